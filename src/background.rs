@@ -0,0 +1,55 @@
+//! This module defines the [`Background`] trait, which should be implemented for the light
+//! arriving from infinitely far away, i.e. seen by rays that hit no entity.
+
+/// Implement [`ConstantBackground`] as a [`Background`].
+mod constant;
+/// Implement [`EnvironmentMap`] as a [`Background`].
+mod environment;
+/// Implement [`GradientBackground`] as a [`Background`].
+mod gradient;
+
+/// Re-export the implemented backgrounds.
+pub use self::{
+    constant::ConstantBackground, environment::EnvironmentMap, gradient::GradientBackground,
+};
+
+use nalgebra as na;
+
+/// Defines a direction sampled towards the background.
+pub struct BackgroundSample {
+    /// The unit direction pointing towards the background.
+    pub direction: na::UnitVector3<f64>,
+    /// The radiance arriving from the direction.
+    pub radiance: na::Vector3<f64>,
+    /// The probability density of the direction, with respect to solid angle.
+    pub pdf: f64,
+}
+
+/// A trait that computes the radiance arriving from infinitely far away.
+pub trait Background: Send + Sync {
+    /// Compute the radiance arriving from the given direction, i.e. seen by a ray travelling
+    /// along `direction`. Note that the direction vector is not necessarily a unit vector.
+    fn radiance(&self, direction: &na::Vector3<f64>) -> na::Vector3<f64>;
+
+    /// Whether the background is sampled as a light source, which requires
+    /// [`Background::sample`] and [`Background::pdf`] to be implemented. Defaults to `false`.
+    fn is_light(&self) -> bool {
+        false
+    }
+
+    /// Sample a direction towards the background as a light source, given two uniform random
+    /// numbers in [0, 1).
+    ///
+    /// Returns `None` if the background does not support light sampling, which is the default.
+    /// Such backgrounds are only reached by rays scattered from surfaces.
+    fn sample(&self, _u: (f64, f64)) -> Option<BackgroundSample> {
+        None
+    }
+
+    /// The probability density (with respect to solid angle) that [`Background::sample`]
+    /// returns the given direction. Note that the direction vector is not necessarily a unit
+    /// vector.
+    fn pdf(&self, _direction: &na::Vector3<f64>) -> f64 {
+        0.
+    }
+}
@@ -0,0 +1,25 @@
+//! Implement the [`ConstantBackground`], which has the same color in all directions.
+
+use super::Background;
+use nalgebra as na;
+
+/// A background with the same radiance in all directions.
+pub struct ConstantBackground {
+    /// The radiance on three color channels.
+    color: na::Vector3<f64>,
+}
+
+impl ConstantBackground {
+    /// Create a new [`ConstantBackground`] with the given radiance.
+    ///
+    /// Use black for scenes lit only by emissive entities.
+    pub fn new(color: na::Vector3<f64>) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for ConstantBackground {
+    fn radiance(&self, _direction: &na::Vector3<f64>) -> na::Vector3<f64> {
+        self.color
+    }
+}
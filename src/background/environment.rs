@@ -0,0 +1,147 @@
+//! Implement the [`EnvironmentMap`], which surrounds the world with an HDR image.
+
+use super::{Background, BackgroundSample};
+use crate::distribution::Distribution2D;
+use crate::entity::{ImageTexture, Texture, WrapMode};
+use crate::utils::luminance;
+use nalgebra as na;
+use std::path::Path;
+
+/// A background given by an image in equirectangular (latitude-longitude) projection.
+///
+/// The top and bottom rows of the image are mapped to the +Y and -Y directions respectively,
+/// and the center of the image is mapped to the -Z direction, before applying the rotation.
+/// High dynamic range images (Radiance `.hdr`, OpenEXR `.exr`) keep their full range, so the
+/// map can light the scene like a studio HDRI.
+///
+/// The map can be sampled as a light source, where directions are drawn proportional to the
+/// luminance of the pixels, so that small bright features such as the sun converge quickly.
+pub struct EnvironmentMap {
+    /// The image, sampled with bilinear filtering.
+    texture: ImageTexture,
+    /// The rotation from the local frame of the map to the world.
+    rotation: na::Rotation3<f64>,
+    /// The scale applied to the radiance.
+    intensity: f64,
+    /// The distribution over the image used to sample directions, where the rows go from top
+    /// to bottom.
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    /// Create a new [`EnvironmentMap`] from an equirectangular image.
+    ///
+    /// Panics if the image is empty.
+    pub fn new(image: image::DynamicImage) -> Self {
+        let texture = ImageTexture::new(image).with_wrap(WrapMode::Repeat, WrapMode::Clamp);
+
+        // Rows near the poles cover smaller solid angles, so their weights are scaled by the
+        // sine of the polar angle.
+        let pixels = texture.image();
+        let (width, height) = pixels.dimensions();
+        let weights: Vec<f64> = pixels
+            .enumerate_pixels()
+            .map(|(_, y, pixel)| {
+                let theta = std::f64::consts::PI * (y as f64 + 0.5) / height as f64;
+                let [r, g, b] = pixel.0;
+                luminance(&na::vector![r, g, b].cast()) * theta.sin()
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width as usize);
+
+        Self {
+            texture,
+            rotation: na::Rotation3::identity(),
+            intensity: 1.,
+            distribution,
+        }
+    }
+
+    /// Load an equirectangular image file, e.g. `.hdr` or `.exr`.
+    pub fn open(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        Ok(Self::new(image::open(path)?))
+    }
+
+    /// Set the rotation of the map. For example, use [`na::Rotation3::from_axis_angle`] around
+    /// the Y axis to turn the map horizontally.
+    pub fn with_rotation(mut self, rotation: na::Rotation3<f64>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set the scale applied to the radiance.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+}
+
+impl EnvironmentMap {
+    /// Map a direction in the world to the position in the image, where both coordinates are in
+    /// [0, 1] and the vertical one goes from top to bottom.
+    fn direction_to_image(&self, direction: &na::Vector3<f64>) -> (f64, f64) {
+        use std::f64::consts::{PI, TAU};
+
+        let local = self
+            .rotation
+            .inverse_transform_vector(direction)
+            .normalize();
+        let phi = f64::atan2(local.x, -local.z);
+        let theta = local.y.clamp(-1., 1.).acos();
+        (0.5 + phi / TAU, theta / PI)
+    }
+
+    /// Map a position in the image back to a unit direction in the world.
+    ///
+    /// Returns the direction and the sine of its polar angle.
+    fn image_to_direction(&self, (x, y): (f64, f64)) -> (na::UnitVector3<f64>, f64) {
+        use std::f64::consts::{PI, TAU};
+
+        let phi = TAU * (x - 0.5);
+        let theta = PI * y;
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let local = na::vector![sin_theta * phi.sin(), cos_theta, -sin_theta * phi.cos()];
+        let direction = na::UnitVector3::new_normalize(self.rotation * local);
+        (direction, sin_theta)
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn radiance(&self, direction: &na::Vector3<f64>) -> na::Vector3<f64> {
+        // The texture coordinates start from the bottom of the image.
+        let (x, y) = self.direction_to_image(direction);
+        let uv = na::point![x, 1. - y];
+        self.intensity * self.texture.value(&uv, &na::Point3::origin())
+    }
+
+    fn is_light(&self) -> bool {
+        true
+    }
+
+    fn sample(&self, u: (f64, f64)) -> Option<BackgroundSample> {
+        use std::f64::consts::PI;
+
+        let (position, pdf) = self.distribution.sample(u);
+        let (direction, sin_theta) = self.image_to_direction(position);
+        if pdf <= 0. || sin_theta <= 0. {
+            return None;
+        }
+        // Convert the density over the image into the density over solid angle.
+        Some(BackgroundSample {
+            direction,
+            radiance: self.radiance(&direction),
+            pdf: pdf / (2. * PI * PI * sin_theta),
+        })
+    }
+
+    fn pdf(&self, direction: &na::Vector3<f64>) -> f64 {
+        use std::f64::consts::PI;
+
+        let (x, y) = self.direction_to_image(direction);
+        let sin_theta = (PI * y).sin();
+        if sin_theta <= 0. {
+            return 0.;
+        }
+        self.distribution.pdf((x.clamp(0., 1.), y)) / (2. * PI * PI * sin_theta)
+    }
+}
@@ -0,0 +1,91 @@
+//! Implement the [`GradientBackground`], which blends two colors from bottom to top.
+
+use super::{Background, BackgroundSample};
+use crate::utils::luminance;
+use nalgebra as na;
+
+/// A background which linearly blends from the bottom color (looking straight down) to the top
+/// color (looking straight up), according to the Y component of the direction.
+///
+/// It is sampled as a light source, with a density proportional to the luminance.
+pub struct GradientBackground {
+    /// The radiance when looking straight down.
+    bottom: na::Vector3<f64>,
+    /// The radiance when looking straight up.
+    top: na::Vector3<f64>,
+}
+
+impl GradientBackground {
+    /// Create a new [`GradientBackground`] with the given colors.
+    pub fn new(bottom: na::Vector3<f64>, top: na::Vector3<f64>) -> Self {
+        Self { bottom, top }
+    }
+
+    /// Create the default sky, which blends from white to light blue.
+    pub fn sky() -> Self {
+        Self::new(na::vector![1., 1., 1.], na::vector![0.5, 0.7, 1.])
+    }
+
+    /// The luminance when looking straight down and straight up, clamped to be non-negative.
+    fn luminances(&self) -> (f64, f64) {
+        (
+            luminance(&self.bottom).max(0.),
+            luminance(&self.top).max(0.),
+        )
+    }
+}
+
+impl Default for GradientBackground {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl Background for GradientBackground {
+    fn radiance(&self, direction: &na::Vector3<f64>) -> na::Vector3<f64> {
+        let alpha = 0.5 * (direction.normalize().y + 1.);
+        (1. - alpha) * self.bottom + alpha * self.top
+    }
+
+    fn is_light(&self) -> bool {
+        let (bottom, top) = self.luminances();
+        bottom + top > 0.
+    }
+
+    fn sample(&self, (u0, u1): (f64, f64)) -> Option<BackgroundSample> {
+        use std::f64::consts::TAU;
+
+        // The luminance is linear in the Y component, and so is the density of `alpha`, whose
+        // cumulative distribution is inverted in a form that is stable when both are equal.
+        let (bottom, top) = self.luminances();
+        let denominator = bottom + (bottom * bottom + u0 * (top * top - bottom * bottom)).sqrt();
+        if denominator <= 0. {
+            return None;
+        }
+        let alpha = (u0 * (bottom + top) / denominator).clamp(0., 1.);
+        let y = 2. * alpha - 1.;
+        let r = (1. - y * y).max(0.).sqrt();
+        let phi = TAU * u1;
+        let direction = na::Unit::new_unchecked(na::vector![r * phi.cos(), y, r * phi.sin()]);
+        let pdf = self.pdf(&direction);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BackgroundSample {
+            direction,
+            radiance: self.radiance(&direction),
+            pdf,
+        })
+    }
+
+    fn pdf(&self, direction: &na::Vector3<f64>) -> f64 {
+        use std::f64::consts::TAU;
+
+        let (bottom, top) = self.luminances();
+        if bottom + top <= 0. {
+            return 0.;
+        }
+        let alpha = 0.5 * (direction.normalize().y + 1.);
+        ((1. - alpha) * bottom + alpha * top) / (TAU * (bottom + top))
+    }
+}
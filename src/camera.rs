@@ -1,11 +1,17 @@
 //! Defines [`Camera`] that renders the world.
 
-use crate::entity::{scattering, Entity};
+use crate::entity::{ScatterKind, World};
+use crate::integrator::{Integrator, PathTracer};
 use crate::ray::Ray;
-use crate::utils::random_in_unit_disk;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use crate::sampler::{Sampler, SamplerKind};
+use crate::utils::{luminance, sample_unit_disk};
+use indicatif::{ProgressBar, ProgressStyle};
 use nalgebra as na;
 use rayon::prelude::*;
+use std::any::Any;
+use std::ops::Range;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
 
 pub struct CameraBuilder {
     // Note: Exactly 2 fields in `image_width`, `image_height`, and `ratio` should be set.
@@ -21,6 +27,14 @@ pub struct CameraBuilder {
     defocus_angle: f64,
     // Quality of rendering.
     sampling: i32,
+    min_sampling: i32,
+    threshold: Option<f64>,
+    time_budget: Option<Duration>,
+    snapshot_interval: Option<Duration>,
+    sampler: SamplerKind,
+    seed: u64,
+    // The minimum and maximum depth of paths for each kind of scattering.
+    depth: [(u32, u32); 3],
 }
 
 impl CameraBuilder {
@@ -37,6 +51,13 @@ impl CameraBuilder {
             focal_dist: 10.,
             defocus_angle: 0.,
             sampling: 200,
+            min_sampling: 16,
+            threshold: None,
+            time_budget: None,
+            snapshot_interval: None,
+            sampler: SamplerKind::default(),
+            seed: 0,
+            depth: PathTracer::DEFAULT_DEPTH,
         }
     }
 }
@@ -108,6 +129,98 @@ impl CameraBuilder {
         self
     }
 
+    /// Sample pixels adaptively, so that each pixel stops being sampled once the standard error
+    /// of its mean luminance is within `threshold` of the square root of the mean, which allows
+    /// more noise in bright pixels, where it is less visible. Pixels are sampled at least
+    /// [`min_sampling`] and at most [`sampling`] times, or until the [`time_budget`] is used up.
+    ///
+    /// Stopping early darkens pixels whose light is rarely found, e.g. caustics, so the minimum
+    /// should be large enough to find it. Only the samples of the pixel itself are considered,
+    /// not light splatted onto it, so integrators that only splat, e.g.
+    /// [`MetropolisLightTransport`], are rendered with the minimum number of samples. Pixels
+    /// are checked after every few samples, so they may be sampled a few more times than needed.
+    /// Panics if `threshold` is not positive.
+    ///
+    /// [`min_sampling`]: Self::min_sampling
+    /// [`sampling`]: Self::sampling
+    /// [`time_budget`]: Self::time_budget
+    /// [`MetropolisLightTransport`]: crate::integrator::MetropolisLightTransport
+    pub fn adaptive(mut self, threshold: f64) -> Self {
+        assert!(
+            threshold > 0.,
+            "CameraBuilder: `threshold` should be positive."
+        );
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Set the minimum number of samples of each pixel for adaptive sampling, 16 by default.
+    ///
+    /// Panics if `sampling` is less than 2, since the error cannot be estimated from fewer
+    /// samples.
+    pub fn min_sampling(mut self, sampling: i32) -> Self {
+        assert!(
+            sampling >= 2,
+            "CameraBuilder: `sampling` should be at least 2."
+        );
+        self.min_sampling = sampling;
+        self
+    }
+
+    /// Render for the given time instead of a fixed number of samples, for the best image within
+    /// the time. Passes are rendered until the time has passed, even beyond [`sampling`], which
+    /// then only sets the number of samples that the sampler spreads evenly over each pixel.
+    /// Passes are rendered in waves, so rendering stops after the wave in progress, which may
+    /// exceed the time a little.
+    ///
+    /// Combined with [`adaptive`] sampling, rendering also stops once the noise of all pixels is
+    /// below its threshold.
+    ///
+    /// [`sampling`]: Self::sampling
+    /// [`adaptive`]: Self::adaptive
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Take a snapshot of the image rendered so far whenever the given time has passed since the
+    /// last one, e.g. to save intermediate images, see [`Camera::render_world_progressive`].
+    pub fn snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = Some(interval);
+        self
+    }
+
+    /// Set the kind of sampler that draws the random numbers of each sample.
+    pub fn sampler(mut self, kind: SamplerKind) -> Self {
+        self.sampler = kind;
+        self
+    }
+
+    /// Set the seed of all random numbers drawn while rendering.
+    ///
+    /// Rendering the same world with the same configuration and seed gives the same image,
+    /// regardless of the number of threads.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the depth limits of paths for a kind of scattering, used by the default integrator,
+    /// see [`Camera::path_tracer`].
+    ///
+    /// Once a path has scattered more than `min` times of this kind, it is randomly terminated
+    /// by Russian roulette based on its throughput, which keeps the result unbiased. A path is
+    /// always terminated after scattering `max` times of this kind.
+    ///
+    /// The default limits are (4, 64) for diffuse scattering, and (8, 128) for specular
+    /// scattering and transmission, so that light passing through glass is rarely cut off.
+    /// Panics if `min` exceeds `max`.
+    pub fn depth(mut self, kind: ScatterKind, min: u32, max: u32) -> Self {
+        assert!(min <= max, "CameraBuilder: `min` should not exceed `max`.");
+        self.depth[kind as usize] = (min, max);
+        self
+    }
+
     /// Build a [`Camera`] with the current configuration.
     pub fn build(self) -> Camera {
         // Get image size options.
@@ -148,8 +261,199 @@ impl CameraBuilder {
             defocus_u: defocus_radius * u_axis,
             defocus_v: defocus_radius * v_axis,
             sampling: self.sampling,
+            // The error of a pixel cannot be estimated from fewer than 2 samples, even if fewer
+            // are rendered in total.
+            min_sampling: self.min_sampling.min(self.sampling).max(2),
+            threshold: self.threshold,
+            time_budget: self.time_budget,
+            snapshot_interval: self.snapshot_interval,
+            sampler: self.sampler,
+            seed: self.seed,
+            depth: self.depth,
+        }
+    }
+}
+
+/// Defines the importance arriving at a reference point from a point sampled on the lens.
+pub struct ImportanceSample {
+    /// The unit direction from the reference point towards the lens.
+    pub direction: na::UnitVector3<f64>,
+    /// The distance from the reference point to the sampled point on the lens.
+    pub distance: f64,
+    /// The importance arriving at the reference point, ignoring occlusion.
+    pub importance: f64,
+    /// The probability density of the direction, with respect to solid angle.
+    ///
+    /// For a lens that is a single point, the density is only formal, and merely the ratio of
+    /// `importance` to `pdf` is meaningful.
+    pub pdf: f64,
+    /// The position on the image (in pixels) that the reference point is seen at.
+    pub pixel: (f64, f64),
+}
+
+/// Defines the light splatted onto arbitrary pixels of the image, shared by all samples rendered
+/// in parallel.
+///
+/// The light is summed in fixed point with atomic integers, so that the sum does not depend on
+/// the order in which samples splat onto the same pixel.
+struct Splats {
+    /// The splatted light, flattened in the same layout as the rendered image.
+    values: Vec<AtomicI64>,
+}
+
+impl Splats {
+    /// The number of units of the fixed point per unit of light.
+    const SCALE: f64 = (1u64 << 32) as f64;
+
+    /// Create new [`Splats`] without any light, for an image of the given number of values.
+    fn new(len: usize) -> Self {
+        Self {
+            values: (0..len).map(|_| AtomicI64::new(0)).collect(),
+        }
+    }
+
+    /// Add light to the value of the given index.
+    ///
+    /// Both the conversion and the sum saturate rather than wrap around, so too much light
+    /// clamps the value. As light is never negative, the sum still does not depend on the order.
+    fn add(&self, index: usize, value: f64) {
+        debug_assert!(value >= 0., "Splats: `value` should be non-negative.");
+        // NaN converts to 0, and is thus dropped in release builds.
+        let value = (value * Self::SCALE).round() as i64;
+        if value == 0 {
+            return;
+        }
+        let _ = self.values[index].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+            Some(sum.saturating_add(value))
+        });
+    }
+
+    /// Obtain the light splatted onto the value of the given index.
+    fn get(&self, index: usize) -> f64 {
+        self.values[index].load(Ordering::Relaxed) as f64 / Self::SCALE
+    }
+}
+
+/// Defines the pass being rendered, onto which light may be splatted at arbitrary pixels of the
+/// image, e.g. by paths traced from lights towards the camera, and any state the integrator
+/// keeps for the pass.
+pub struct Film<'a> {
+    /// The camera that renders the image.
+    camera: &'a Camera,
+    /// The index of the pass, counting from 0.
+    pass: usize,
+    /// The splatted light, shared by all passes.
+    splats: &'a Splats,
+    /// The state prepared by the integrator for the pass, e.g. photons traced from lights.
+    state: Option<Box<dyn Any + Send + Sync>>,
+}
+
+impl<'a> Film<'a> {
+    /// Create a new [`Film`] for the given pass of the camera, splatting onto the given light.
+    fn new(camera: &'a Camera, pass: usize, splats: &'a Splats) -> Self {
+        Self {
+            camera,
+            pass,
+            splats,
+            state: None,
+        }
+    }
+
+    /// Obtain the camera that renders the image.
+    pub fn camera(&self) -> &'a Camera {
+        self.camera
+    }
+
+    /// Obtain the index of the pass rendered onto the film, counting from 0.
+    pub fn pass(&self) -> usize {
+        self.pass
+    }
+
+    /// Obtain the state kept for the pass, if it has been set with the given type.
+    pub fn state<T: 'static>(&self) -> Option<&T> {
+        self.state.as_ref()?.downcast_ref()
+    }
+
+    /// Keep the given state for the pass, replacing any previous one.
+    pub fn set_state<T: Send + Sync + 'static>(&mut self, state: T) {
+        self.state = Some(Box::new(state));
+    }
+
+    /// Add light to the pixel at the given position on the image (in pixels). Positions outside
+    /// the image are ignored.
+    pub fn splat(&self, (x, y): (f64, f64), color: &na::Vector3<f64>) {
+        let (width, height) = (self.camera.image_width, self.camera.image_height);
+        if !(0. ..width as f64).contains(&x) || !(0. ..height as f64).contains(&y) {
+            return;
+        }
+        let index = ((y as u32).min(height - 1) * width + (x as u32).min(width - 1)) as usize * 3;
+        color
+            .iter()
+            .enumerate()
+            .for_each(|(i, &c)| self.splats.add(index + i, c));
+    }
+}
+
+/// Defines the light accumulated in a pixel, with the statistics of its luminance for adaptive
+/// sampling.
+#[derive(Clone, Default)]
+struct Pixel {
+    /// The light summed over all samples rendered so far.
+    sum: na::Vector3<f64>,
+    /// The number of samples rendered so far.
+    samples: usize,
+    /// The mean luminance of the samples.
+    mean: f64,
+    /// The sum of squared differences of the luminance of the samples from their mean.
+    m2: f64,
+}
+
+impl Pixel {
+    /// Add the light of a sample, updating the statistics of its luminance (Welford's
+    /// algorithm).
+    fn add(&mut self, color: &na::Vector3<f64>) {
+        let luminance = luminance(color);
+        self.sum += color;
+        self.samples += 1;
+        let delta = luminance - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.m2 += delta * (luminance - self.mean);
+    }
+
+    /// Estimate the standard error of the mean luminance.
+    fn error(&self) -> f64 {
+        let n = self.samples as f64;
+        (self.m2 / ((n - 1.) * n)).sqrt()
+    }
+}
+
+/// Defines a rectangular region of the image, and the light accumulated in its pixels.
+struct Tile {
+    /// The columns of the pixels in the tile.
+    xs: Range<u32>,
+    /// The rows of the pixels in the tile.
+    ys: Range<u32>,
+    /// The light accumulated in the pixels, row by row.
+    pixels: Vec<Pixel>,
+}
+
+impl Tile {
+    /// Create a new [`Tile`] without any light, covering the given columns and rows.
+    fn new(xs: Range<u32>, ys: Range<u32>) -> Self {
+        let len = xs.len() * ys.len();
+        Self {
+            xs,
+            ys,
+            pixels: vec![Pixel::default(); len],
         }
     }
+
+    /// Iterate over the positions of the pixels in the tile, row by row.
+    fn positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.ys
+            .clone()
+            .flat_map(|y| self.xs.clone().map(move |x| (x, y)))
+    }
 }
 
 /// Defines the configuration of the world camera.
@@ -175,14 +479,44 @@ pub struct Camera {
     defocus_v: na::Vector3<f64>,
     /// Quality of rendering.
     sampling: i32,
+    /// The minimum number of samples of each pixel for adaptive sampling.
+    min_sampling: i32,
+    /// The error below which pixels stop being sampled, or `None` to sample all pixels equally.
+    threshold: Option<f64>,
+    /// The time to render for, regardless of `sampling`, or `None` to render all samples.
+    time_budget: Option<Duration>,
+    /// The time between snapshots of the image while rendering, or `None` for no snapshots.
+    snapshot_interval: Option<Duration>,
+    /// The kind of sampler that draws the random numbers of each sample.
+    sampler: SamplerKind,
+    /// The seed of all random numbers drawn while rendering.
+    seed: u64,
+    /// The minimum and maximum depth of paths for each [`ScatterKind`].
+    depth: [(u32, u32); 3],
 }
 
 impl Camera {
-    /// Maximum number of scatters before the ray disappears.
-    const MAX_SCATTER: i32 = 50;
     /// Style of the progress bar.
     const PB_STYLE: &'static str =
-        "Rendering {prefix:>4}: {wide_bar:.green/yellow} {pos:>7}/{len:7} {elapsed_precise}/{duration_precise}";
+        "Rendering: {wide_bar:.green/yellow} {pos:>10}/{len:10} {elapsed_precise}/{duration_precise}";
+
+    /// Style of the progress bar when the number of samples is not known in advance, i.e. with
+    /// a time budget or adaptive sampling.
+    const PB_STYLE_UNBOUNDED: &'static str =
+        "Rendering: {spinner:.green} {pos:>10} {elapsed_precise}";
+
+    /// The width and height of the tiles that the image is divided into, in pixels.
+    const TILE_SIZE: u32 = 16;
+
+    /// The number of passes prepared at once, before rendering their samples in all tiles.
+    ///
+    /// The number is fixed, rather than depending on the number of threads, so that adaptive
+    /// sampling stops after the same pass regardless of the threads.
+    const WAVE_SIZE: usize = 8;
+
+    /// The pixel that the numbers drawn before the samples of each pass are attributed to, so
+    /// they do not follow the pattern of any actual pixel.
+    const PASS_PIXEL: (u32, u32) = (u32::MAX, u32::MAX);
 }
 
 impl Camera {
@@ -195,43 +529,136 @@ impl Camera {
     pub fn height(&self) -> u32 {
         self.image_height
     }
+
+    /// Obtain the seed of all random numbers drawn while rendering.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Create the default integrator, a [`PathTracer`] with the depth limits of the camera.
+    pub fn path_tracer(&self) -> PathTracer {
+        let kinds = [
+            ScatterKind::Diffuse,
+            ScatterKind::Specular,
+            ScatterKind::Transmission,
+        ];
+        kinds.into_iter().fold(PathTracer::new(), |tracer, kind| {
+            let (min, max) = self.depth[kind as usize];
+            tracer.with_depth(kind, min, max)
+        })
+    }
 }
 
 impl Camera {
-    /// Render a ray which interacts with given objects.
-    fn render_ray(ray: Ray, objects: &[Entity]) -> na::Vector3<f64> {
-        // Record the current decay factor.
-        let mut color = na::vector![1., 1., 1.];
-        // Record the current ray.
-        let mut light = ray;
-        // Iterate at most `MAX_SCATTER` times.
-        for _ in 0..Self::MAX_SCATTER {
-            if let Some(ray) = scattering(objects, &light, (f64::EPSILON, f64::INFINITY)) {
-                // Foreground objects.
-                if ray.decay.iter().all(|&c| c < 1e-8) {
-                    return na::vector![0., 0., 0.];
-                }
-                color.component_mul_assign(&ray.decay);
-                light = ray.ray;
-            } else {
-                // Background
-                let alpha = 0.5 * (light.direction.normalize().y + 1.);
-                let bg = (1. - alpha) * na::vector![1., 1., 1.] + alpha * na::vector![0.5, 0.7, 1.];
-                return color.component_mul(&bg);
-            }
+    /// The unit direction the camera looks to.
+    pub fn forward(&self) -> na::UnitVector3<f64> {
+        na::UnitVector3::new_normalize(self.pixel_du.cross(&self.pixel_dv))
+    }
+
+    /// The distance from the camera center to the viewport, which lies on the focal plane.
+    fn focal_dist(&self) -> f64 {
+        (self.base_pixel_loc - self.center).dot(&self.forward())
+    }
+
+    /// The area of the viewport.
+    fn viewport_area(&self) -> f64 {
+        self.pixel_du.norm()
+            * self.image_width as f64
+            * self.pixel_dv.norm()
+            * self.image_height as f64
+    }
+
+    /// The area of the lens, or 1 if the lens is a single point (i.e. without defocus blur).
+    fn lens_area(&self) -> f64 {
+        let radius = self.defocus_u.norm();
+        if radius > 0. {
+            std::f64::consts::PI * radius * radius
+        } else {
+            1.
+        }
+    }
+
+    /// Find the position on the image (in pixels, not necessarily integers) that the ray from
+    /// a point on the lens reaches.
+    ///
+    /// Returns `None` if the ray does not reach the image.
+    pub fn raster(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let forward = self.forward();
+        let speed = ray.direction.dot(&forward);
+        if speed <= 0. {
+            return None;
         }
-        // Return black if the ray scatters too many times.
-        na::vector![0., 0., 0.]
+        let offset = ray.at(self.focal_dist() / speed) - self.base_pixel_loc;
+        let x = offset.dot(&self.pixel_du) / self.pixel_du.norm_squared();
+        let y = offset.dot(&self.pixel_dv) / self.pixel_dv.norm_squared();
+        let inside = (0. ..self.image_width as f64).contains(&x)
+            && (0. ..self.image_height as f64).contains(&y);
+        inside.then_some((x, y))
     }
 
-    /// Sample a ray to render the given pixel.
+    /// Compute the importance emitted by the camera along the ray from a point on the lens,
+    /// i.e. how much light arriving along the opposite direction contributes to the image.
+    ///
+    /// This is the counterpart of radiance for paths traced from lights towards the camera.
+    pub fn importance(&self, ray: &Ray) -> f64 {
+        if self.raster(ray).is_none() {
+            return 0.;
+        }
+        let cosine = ray.direction.normalize().dot(&self.forward());
+        self.focal_dist().powi(2) / (self.viewport_area() * self.lens_area() * cosine.powi(4))
+    }
+
+    /// The probability densities of the origin (with respect to area) and the direction (with
+    /// respect to solid angle) that the camera samples the given ray to render the image.
+    pub fn pdf_ray(&self, ray: &Ray) -> (f64, f64) {
+        if self.raster(ray).is_none() {
+            return (0., 0.);
+        }
+        let cosine = ray.direction.normalize().dot(&self.forward());
+        let pdf_direction = self.focal_dist().powi(2) / (self.viewport_area() * cosine.powi(3));
+        (1. / self.lens_area(), pdf_direction)
+    }
+
+    /// Sample a point on the lens as seen from the reference point, given two uniform random
+    /// numbers in [0, 1).
+    ///
+    /// Returns `None` if the reference point is not seen on the image from the point.
+    pub fn sample_importance(
+        &self,
+        reference: &na::Point3<f64>,
+        (u0, u1): (f64, f64),
+    ) -> Option<ImportanceSample> {
+        let (r, phi) = (u0.sqrt(), std::f64::consts::TAU * u1);
+        let lens = self.center + r * (phi.cos() * self.defocus_u + phi.sin() * self.defocus_v);
+        let ray = Ray::new(lens, reference - lens);
+        let pixel = self.raster(&ray)?;
+        let (direction, distance) = na::Unit::try_new_and_get(lens - reference, 0.)?;
+        let cosine = -direction.dot(&self.forward());
+        Some(ImportanceSample {
+            direction,
+            distance,
+            importance: self.importance(&ray),
+            pdf: distance * distance / (cosine * self.lens_area()),
+            pixel,
+        })
+    }
+}
+
+impl Camera {
+    /// Sample a ray to render the given pixel, drawing the position in the pixel and on the
+    /// lens from the sampler.
     /// The ray should start from the camera center and point to the pixel.
-    fn sample_ray(&self, x: u32, y: u32) -> Ray {
-        let (delta_x, delta_y) = random_in_unit_disk();
+    fn sample_ray(&self, x: u32, y: u32, sampler: &mut dyn Sampler) -> Ray {
+        let (u0, u1) = sampler.next_2d();
+        self.generate_ray((x as f64 + u0, y as f64 + u1), sampler.next_2d())
+    }
+
+    /// Generate the ray through the given position on the image (in pixels), starting from the
+    /// point on the lens given by two numbers in [0, 1).
+    pub fn generate_ray(&self, (x, y): (f64, f64), lens: (f64, f64)) -> Ray {
+        let (delta_x, delta_y) = sample_unit_disk(lens);
         let source = self.center + delta_x * self.defocus_u + delta_y * self.defocus_v;
-        let target = self.base_pixel_loc
-            + (x as f64 + rand::random::<f64>()) * self.pixel_du
-            + (y as f64 + rand::random::<f64>()) * self.pixel_dv;
+        let target = self.base_pixel_loc + x * self.pixel_du + y * self.pixel_dv;
         Ray {
             origin: source,
             direction: target - source,
@@ -240,43 +667,234 @@ impl Camera {
 }
 
 impl Camera {
-    /// Render whole image with given objects.
+    /// Divide the image into tiles, row by row.
+    fn tiles(&self) -> Vec<Tile> {
+        let (width, height) = (self.image_width, self.image_height);
+        (0..height)
+            .step_by(Self::TILE_SIZE as usize)
+            .flat_map(|y| {
+                (0..width).step_by(Self::TILE_SIZE as usize).map(move |x| {
+                    Tile::new(
+                        x..(x + Self::TILE_SIZE).min(width),
+                        y..(y + Self::TILE_SIZE).min(height),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Decide whether the pixel has been sampled enough, which is never the case without
+    /// adaptive sampling.
+    fn converged(&self, pixel: &Pixel) -> bool {
+        self.threshold.is_some_and(|threshold| {
+            pixel.samples >= self.min_sampling as usize
+                && pixel.error() <= threshold * pixel.mean.max(0.).sqrt()
+        })
+    }
+
+    /// Render the samples of the given passes in the pixels of the tile that have not converged,
+    /// adding their light to the pixels in the order of passes.
     ///
-    /// This function only render one pass, and return a flattened vector of shape [H, W, 3],
-    /// where each element is in [0.0, 1.0) and each pixel is RGB format.
-    pub fn render_world_single(&self, objects: &[Entity], pb: ProgressBar) -> na::DVector<f64> {
-        let tid = rayon::current_thread_index().unwrap();
-        core_affinity::set_for_current(core_affinity::CoreId { id: tid });
-
-        let mut image_buf = Vec::with_capacity((self.image_width * self.image_height * 3) as usize);
-        for y in 0..self.image_height {
-            for x in 0..self.image_width {
-                let color = Self::render_ray(self.sample_ray(x, y), objects);
-                image_buf.extend_from_slice(color.as_slice());
-                pb.inc(1);
+    /// Pixels are only checked for convergence before the first pass, so each pixel renders the
+    /// samples of either all or none of the passes.
+    /// Return the number of samples rendered.
+    fn render_tile(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        tile: &mut Tile,
+        films: &[Film],
+    ) -> usize {
+        // Tiles may also run on the calling thread, which is not pinned to any core.
+        if let Some(tid) = rayon::current_thread_index() {
+            core_affinity::set_for_current(core_affinity::CoreId { id: tid });
+        }
+
+        let active: Vec<_> = tile
+            .positions()
+            .zip(&tile.pixels)
+            .enumerate()
+            .filter(|(_, (_, pixel))| !self.converged(pixel))
+            .map(|(i, (position, _))| (i, position))
+            .collect();
+        let mut sampler = self.sampler.create(self.sampling as usize, self.seed);
+        for film in films {
+            for &(i, (x, y)) in &active {
+                sampler.start_pixel_sample((x, y), film.pass());
+                let ray = self.sample_ray(x, y, sampler.as_mut());
+                let color = integrator.radiance(&ray, world, film, sampler.as_mut());
+                tile.pixels[i].add(&color);
+            }
+        }
+        active.len() * films.len()
+    }
+
+    /// Average the light accumulated in the tiles and splatted so far into an image.
+    fn resolve(&self, tiles: &[Tile], splats: &Splats) -> na::DVector<f64> {
+        let pixels = (self.image_width * self.image_height) as usize;
+
+        // Average the light of each pixel over its own samples, and the splatted light over the
+        // average number of samples per pixel, as each sample may splat light onto any pixel.
+        // Without adaptive sampling, both are the number of passes.
+        let samples: usize = tiles
+            .iter()
+            .flat_map(|tile| &tile.pixels)
+            .map(|pixel| pixel.samples)
+            .sum();
+        let scale = (samples as f64 / pixels as f64).max(1.);
+        let mut image = na::DVector::from_fn(pixels * 3, |i, _| splats.get(i) / scale);
+        for tile in tiles {
+            for (pixel, (x, y)) in tile.pixels.iter().zip(tile.positions()) {
+                let index = (y * self.image_width + x) as usize * 3;
+                let color = pixel.sum / pixel.samples.max(1) as f64;
+                for c in 0..3 {
+                    image[index + c] += color[c];
+                }
+            }
+        }
+        image
+    }
+
+    /// Render whole image with the given world, estimating the light of each sample with the
+    /// given integrator.
+    ///
+    /// Return a flattened vector of shape [H, W, 3], where each pixel is in RGB format.
+    pub fn render_world(&self, world: &World, integrator: &dyn Integrator) -> na::DVector<f64> {
+        self.render_world_progressive(world, integrator, |_| {})
+    }
+
+    /// Render whole image with the given world, estimating the light of each sample with the
+    /// given integrator, and pass the image rendered so far to `snapshot` at every
+    /// [snapshot interval], e.g. to save it, so that an interrupted render leaves usable output.
+    ///
+    /// Passes are rendered in waves of a few passes. Each pass of a wave is prepared by the
+    /// integrator in parallel, and then the tiles of the image are rendered in parallel, each
+    /// with the samples of all passes of the wave. Rendering stops after the wave that renders
+    /// the last pass (unless a time budget is set), leaves all pixels converged with adaptive
+    /// sampling, or uses up the time budget.
+    ///
+    /// Return a flattened vector of shape [H, W, 3], where each pixel is in RGB format.
+    ///
+    /// [snapshot interval]: CameraBuilder::snapshot_interval
+    pub fn render_world_progressive(
+        &self,
+        world: &World,
+        integrator: &dyn Integrator,
+        mut snapshot: impl FnMut(&na::DVector<f64>),
+    ) -> na::DVector<f64> {
+        let start = Instant::now();
+        let mut last_snapshot = start;
+        let pixels = (self.image_width * self.image_height) as usize;
+        // With a time budget, passes are rendered until the time has passed.
+        let limit = match self.time_budget {
+            Some(_) => usize::MAX,
+            None => self.sampling as usize,
+        };
+        // Adaptive sampling may stop at any pass, so the bar only counts the samples.
+        let pb = if self.time_budget.is_some() || self.threshold.is_some() {
+            let style = ProgressStyle::with_template(Self::PB_STYLE_UNBOUNDED).unwrap();
+            ProgressBar::no_length().with_style(style)
+        } else {
+            let style = ProgressStyle::with_template(Self::PB_STYLE).unwrap();
+            ProgressBar::new((pixels * self.sampling as usize) as u64).with_style(style)
+        };
+
+        let splats = Splats::new(pixels * 3);
+        let mut tiles = self.tiles();
+        let mut passes = 0;
+        while passes < limit {
+            let end = (passes + Self::WAVE_SIZE).min(limit);
+            let films: Vec<_> = (passes..end)
+                .into_par_iter()
+                .map(|pass| {
+                    let mut film = Film::new(self, pass, &splats);
+                    let mut sampler = self.sampler.create(self.sampling as usize, self.seed);
+                    sampler.start_pixel_sample(Self::PASS_PIXEL, pass);
+                    integrator.begin_pass(world, &mut film, sampler.as_mut());
+                    film
+                })
+                .collect();
+            tiles.par_iter_mut().for_each(|tile| {
+                let samples = self.render_tile(world, integrator, tile, &films);
+                pb.inc(samples as u64);
+            });
+            passes = end;
+
+            let converged = |tile: &Tile| tile.pixels.iter().all(|pixel| self.converged(pixel));
+            if tiles.iter().all(converged)
+                || self
+                    .time_budget
+                    .is_some_and(|budget| start.elapsed() >= budget)
+            {
+                break;
+            }
+            if self
+                .snapshot_interval
+                .is_some_and(|interval| last_snapshot.elapsed() >= interval)
+            {
+                snapshot(&self.resolve(&tiles, &splats));
+                last_snapshot = Instant::now();
             }
         }
         pb.finish();
-        na::DVector::from_vec(image_buf)
+        self.resolve(&tiles, &splats)
     }
+}
 
-    /// Render whole image with given objects.
-    pub fn render_world(&self, objects: &[Entity]) -> na::DVector<f64> {
-        let mpb = MultiProgress::new();
-        let style = ProgressStyle::with_template(Self::PB_STYLE).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Dielectric, DiffuseLight, Entity, Lambertian, Sphere};
+    use crate::integrator::{BidirectionalPathTracer, PhotonMapper};
 
-        (0..self.sampling)
-            .into_par_iter()
-            .map(|i| {
-                let pb = ProgressBar::new((self.image_width * self.image_height) as u64)
-                    .with_style(style.clone())
-                    .with_prefix(i.to_string());
-                self.render_world_single(objects, mpb.add(pb))
-            })
-            .reduce(
-                || na::DVector::zeros((self.image_width * self.image_height * 3) as usize),
-                std::ops::Add::add,
-            )
-            .unscale(self.sampling as f64)
+    /// Build a tiny scene with a diffuse floor, a glass sphere, and a small light.
+    fn scene() -> (Camera, World) {
+        let camera = CameraBuilder::new()
+            .sampling(2)
+            .seed(7)
+            .image_width(40)
+            .ratio(1.)
+            .look_from(na::point![0., 1., 5.])
+            .look_at(na::point![0., 0.5, 0.])
+            .view_angle(std::f64::consts::PI / 4.)
+            .build();
+        let world = World::new(vec![
+            Entity::new(
+                Box::new(Sphere::new(100., na::point![0., -100., 0.])),
+                Box::new(Lambertian::new(na::vector![0.5, 0.5, 0.5])),
+            ),
+            Entity::new(
+                Box::new(Sphere::new(0.5, na::point![0., 0.5, 0.])),
+                Box::new(Dielectric::new(na::vector![1., 1., 1.], 1.5)),
+            ),
+            Entity::new(
+                Box::new(Sphere::new(0.2, na::point![0.5, 2., 0.5])),
+                Box::new(DiffuseLight::new(na::vector![20., 20., 20.])),
+            ),
+        ]);
+        (camera, world)
+    }
+
+    /// Render the scene, which spans several tiles, in a pool of the given number of threads.
+    fn render(integrator: &dyn Integrator, threads: usize) -> na::DVector<f64> {
+        let (camera, world) = scene();
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| camera.render_world(&world, integrator))
+    }
+
+    #[test]
+    fn independent_of_threads() {
+        let integrators: [Box<dyn Integrator>; 2] = [
+            Box::new(BidirectionalPathTracer::new()),
+            Box::new(PhotonMapper::new().with_photons(256)),
+        ];
+        for integrator in &integrators {
+            let single = render(integrator.as_ref(), 1);
+            assert!(single.iter().any(|&c| c > 0.));
+            assert_eq!(single, render(integrator.as_ref(), 4));
+        }
     }
 }
@@ -0,0 +1,148 @@
+//! Defines piecewise-constant distributions, which draw samples proportional to a tabulated
+//! function.
+
+/// A piecewise-constant distribution over [0, 1), proportional to a tabulated function.
+pub struct Distribution1D {
+    /// The non-negative function values, one per interval of equal width.
+    func: Vec<f64>,
+    /// The cumulative distribution at the boundaries of the intervals, with `cdf[0] = 0` and
+    /// `cdf[n] = 1`.
+    cdf: Vec<f64>,
+    /// The integral of the function over [0, 1).
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Create a new [`Distribution1D`] proportional to the given function values.
+    ///
+    /// If the function is zero everywhere, the distribution falls back to the uniform one.
+    /// Panics if `func` is empty.
+    pub fn new(func: Vec<f64>) -> Self {
+        assert!(
+            !func.is_empty(),
+            "Distribution1D: the function should not be empty."
+        );
+        let n = func.len() as f64;
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        cdf.push(0.);
+        for &f in &func {
+            cdf.push(cdf.last().unwrap() + f / n);
+        }
+        let integral = *cdf.last().unwrap();
+        if integral > 0. {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        } else {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / n);
+        }
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+}
+
+impl Distribution1D {
+    /// The number of intervals.
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    /// Whether the distribution has no interval. This is never true.
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    /// The integral of the function over [0, 1).
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Draw a sample in [0, 1) given a uniform random number `u` in [0, 1).
+    ///
+    /// Returns the sample, its probability density and the index of the interval it lies in.
+    pub fn sample(&self, u: f64) -> (f64, f64, usize) {
+        // Find the last interval whose lower boundary does not exceed `u`.
+        let index = self.cdf.partition_point(|&c| c <= u).clamp(1, self.len()) - 1;
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0. {
+            (u - self.cdf[index]) / width
+        } else {
+            0.
+        };
+        let x = ((index as f64 + offset) / self.len() as f64).min(1. - f64::EPSILON);
+        (x, self.pdf(index), index)
+    }
+
+    /// Draw the index of an interval with probability proportional to its function value,
+    /// given a uniform random number `u` in [0, 1).
+    ///
+    /// Returns the index and its probability.
+    pub fn sample_discrete(&self, u: f64) -> (usize, f64) {
+        let (_, _, index) = self.sample(u);
+        (index, self.cdf[index + 1] - self.cdf[index])
+    }
+
+    /// The probability density of samples in the interval of the given index.
+    pub fn pdf(&self, index: usize) -> f64 {
+        if self.integral > 0. {
+            self.func[index] / self.integral
+        } else {
+            1.
+        }
+    }
+}
+
+/// A piecewise-constant distribution over [0, 1)², proportional to a tabulated function.
+///
+/// Samples are drawn by first choosing a row from the marginal distribution, and then a
+/// position within the row from its conditional distribution.
+pub struct Distribution2D {
+    /// The conditional distributions along X, one per row.
+    conditional: Vec<Distribution1D>,
+    /// The marginal distribution along Y.
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Create a new [`Distribution2D`] from function values stored row by row, with `width`
+    /// values in each row.
+    ///
+    /// Panics if the function is empty or its length is not a multiple of `width`.
+    pub fn new(func: &[f64], width: usize) -> Self {
+        assert!(
+            width > 0 && !func.is_empty() && func.len().is_multiple_of(width),
+            "Distribution2D: the function should consist of complete rows."
+        );
+        let conditional: Vec<_> = func
+            .chunks_exact(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|d| d.integral()).collect());
+        Self {
+            conditional,
+            marginal,
+        }
+    }
+}
+
+impl Distribution2D {
+    /// Draw a sample in [0, 1)² given two uniform random numbers in [0, 1).
+    ///
+    /// Returns the sample `(x, y)` and its probability density.
+    pub fn sample(&self, (u0, u1): (f64, f64)) -> ((f64, f64), f64) {
+        let (y, pdf_y, row) = self.marginal.sample(u1);
+        let (x, pdf_x, _) = self.conditional[row].sample(u0);
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    /// The probability density of the sample `(x, y)`.
+    pub fn pdf(&self, (x, y): (f64, f64)) -> f64 {
+        let row = ((y * self.marginal.len() as f64) as usize).min(self.marginal.len() - 1);
+        let conditional = &self.conditional[row];
+        let column = ((x * conditional.len() as f64) as usize).min(conditional.len() - 1);
+        self.marginal.pdf(row) * conditional.pdf(column)
+    }
+}
@@ -1,19 +1,30 @@
 //! This module defines all ray tracing related properties of entities in the world.
-//! Please refer to [`geometry`] and [`material`] for more details.
+//! Please refer to [`geometry`], [`material`] and [`texture`] for more details.
 
+/// The bounding volume hierarchy accelerates finding the entity that a ray hits.
+mod bvh;
 /// The geometric property tells us how a ray hits the entity.
 mod geometry;
 /// The material property tells us how the ray is scattered after hitting the entity.
 mod material;
+/// The texture property tells us the color of the surface of the entity.
+mod texture;
 
-/// Re-export the geometry and material traits and implementations.
+/// Re-export the geometry, material and texture traits and implementations.
 pub use self::{
-    geometry::{Geometry, Sphere},
-    material::{Dielectric, Lambertian, Material, Metal},
+    bvh::Bvh,
+    geometry::{
+        Aabb, Disk, Geometry, GeometryHit, GeometrySample, Quad, Sphere, Triangle, TriangleMesh,
+    },
+    material::{Dielectric, DiffuseLight, Lambertian, Material, Metal, ScatterKind, ScatteredRay},
+    texture::{Checker, ImageTexture, SolidColor, Texture, WrapMode},
 };
 
-use crate::entity::material::ScatteredRay;
+use crate::background::{Background, GradientBackground};
+use crate::light::{AreaLight, BackgroundLight, Light, LightSample};
 use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
 
 /// An [`Entity`] should consists of geometry and material.
 pub struct Entity {
@@ -28,26 +39,205 @@ impl Entity {
     pub fn new(geometry: Box<dyn Geometry>, material: Box<dyn Material>) -> Self {
         Self { geometry, material }
     }
+
+    /// Obtain the geometry of the entity.
+    pub fn geometry(&self) -> &dyn Geometry {
+        self.geometry.as_ref()
+    }
+
+    /// Obtain the material of the entity.
+    pub fn material(&self) -> &dyn Material {
+        self.material.as_ref()
+    }
+}
+
+/// A [`World`] is a collection of entities, organized for fast ray queries.
+pub struct World {
+    /// All entities in the world.
+    entities: Vec<Entity>,
+    /// The bounding volume hierarchy over `entities`.
+    bvh: Bvh,
+    /// The light arriving from infinitely far away.
+    background: Box<dyn Background>,
+    /// The lights sampled directly from surfaces, including emissive entities, the background
+    /// (if it is a light), and lights added with [`World::with_light`].
+    lights: Vec<Box<dyn Light>>,
+    /// The index in `lights` of the light of each entity, if the entity emits light.
+    entity_lights: Vec<Option<usize>>,
+    /// The index in `lights` of the background, if it is sampled as a light.
+    background_light: Option<usize>,
+}
+
+impl World {
+    /// Create a new [`World`] with the given entities.
+    pub fn new(entities: Vec<Entity>) -> Self {
+        let boxes: Vec<Aabb> = entities
+            .iter()
+            .map(|entity| entity.geometry.bounding_box())
+            .collect();
+        let bvh = Bvh::new(&boxes);
+
+        // Every emissive entity is sampled as an area light.
+        let mut lights: Vec<Box<dyn Light>> = Vec::new();
+        let entity_lights = (0..entities.len())
+            .map(|i| {
+                entities[i].material.is_emissive().then(|| {
+                    lights.push(Box::new(AreaLight::new(i)));
+                    lights.len() - 1
+                })
+            })
+            .collect();
+        // The default sky is sampled as a light as well.
+        lights.push(Box::new(BackgroundLight));
+        let background_light = Some(lights.len() - 1);
+        Self {
+            entities,
+            bvh,
+            background: Box::new(GradientBackground::sky()),
+            lights,
+            entity_lights,
+            background_light,
+        }
+    }
+
+    /// Set the background instead of the default sky gradient.
+    pub fn with_background(mut self, background: impl Background + 'static) -> Self {
+        if let Some(index) = self.background_light.take() {
+            self.lights.remove(index);
+        }
+        if background.is_light() {
+            self.lights.push(Box::new(BackgroundLight));
+            self.background_light = Some(self.lights.len() - 1);
+        }
+        self.background = Box::new(background);
+        self
+    }
+
+    /// Add a light that is not an entity, e.g. a [`PointLight`].
+    ///
+    /// [`PointLight`]: crate::light::PointLight
+    pub fn with_light(mut self, light: impl Light + 'static) -> Self {
+        self.lights.push(Box::new(light));
+        self
+    }
+}
+
+impl World {
+    /// Obtain all entities in the world.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Obtain the background, which is seen by rays hitting no entity.
+    pub fn background(&self) -> &dyn Background {
+        self.background.as_ref()
+    }
+
+    /// Compute a sphere that bounds all entities, given as its center and radius.
+    ///
+    /// Lights infinitely far away emit light into the world through a disk of this radius.
+    pub fn bounding_sphere(&self) -> (na::Point3<f64>, f64) {
+        let bbox = self.bvh.bounding_box();
+        if bbox.is_empty() {
+            return (na::Point3::origin(), 0.);
+        }
+        (bbox.centroid(), bbox.extent().norm() / 2.)
+    }
+
+    /// Find the nearest entity that the ray hits within the specified range.
+    ///
+    /// Returns the index of the entity together with the hit record.
+    pub fn hit(&self, ray: &Ray, t_range: (f64, f64)) -> Option<(usize, GeometryHit)> {
+        self.bvh.hit(ray, t_range, |i, t_range| {
+            self.entities[i].geometry.hit(ray, t_range)
+        })
+    }
+
+    /// Judge whether any entity blocks the ray within the specified range, e.g. a shadow ray
+    /// towards a light. This is cheaper than [`World::hit`] since any hit will do.
+    pub fn occluded(&self, ray: &Ray, t_range: (f64, f64)) -> bool {
+        self.bvh.occluded(ray, t_range, |i, t_range| {
+            self.entities[i].geometry.hit(ray, t_range).is_some()
+        })
+    }
+}
+
+impl World {
+    /// Obtain all lights that are sampled directly from surfaces.
+    pub fn lights(&self) -> &[Box<dyn Light>] {
+        &self.lights
+    }
+
+    /// Obtain the index in [`World::lights`] of the light of the entity of the given index, or
+    /// the background if `entity` is `None`.
+    ///
+    /// Returns `None` if it is not sampled as a light.
+    pub fn light_index(&self, entity: Option<usize>) -> Option<usize> {
+        match entity {
+            Some(i) => self.entity_lights[i],
+            None => self.background_light,
+        }
+    }
+
+    /// Sample the light arriving at the reference point, given a uniform random number `select`
+    /// to choose one of the lights and two more for [`Light::sample`].
+    ///
+    /// The density of the returned sample includes the probability of choosing the light.
+    pub fn sample_light(
+        &self,
+        reference: &na::Point3<f64>,
+        select: f64,
+        u: (f64, f64),
+    ) -> Option<LightSample> {
+        let count = self.lights.len();
+        let index = ((select * count as f64) as usize).min(count.checked_sub(1)?);
+        let mut sample = self.lights[index].sample(self, reference, u)?;
+        sample.pdf /= count as f64;
+        Some(sample)
+    }
+
+    /// The probability density (with respect to solid angle) that [`World::sample_light`]
+    /// returns the given direction from the reference point, when the ray along it reaches the
+    /// entity of the given index, or the background if `entity` is `None`.
+    pub fn light_pdf(
+        &self,
+        reference: &na::Point3<f64>,
+        direction: &na::Vector3<f64>,
+        entity: Option<usize>,
+    ) -> f64 {
+        self.light_index(entity).map_or(0., |index| {
+            self.lights[index].pdf(self, reference, direction) / self.lights.len() as f64
+        })
+    }
 }
 
-/// Compute the one-step scattering of a ray on the given entities.
+/// Defines the interaction of a ray with the surface of an entity.
+pub struct Scattering {
+    /// The radiance emitted from the surface towards the origin of the ray.
+    pub emitted: na::Vector3<f64>,
+    /// The scattered ray, or `None` if the ray is absorbed.
+    pub scattered: Option<ScatteredRay>,
+}
+
+/// Compute the one-step scattering of a ray on the given world, drawing the random numbers from
+/// the sampler.
+///
+/// Returns `None` if the ray hits nothing.
 ///
 /// Note: Currently, I choose to implement this as a function instead of a method.
 /// I'll keep this until I find out how this can be generalized into a trait/struct.
 pub fn scattering(
-    entities: &[Entity],
+    world: &World,
     ray: &Ray,
-    (min_t, mut max_t): (f64, f64),
-) -> Option<ScatteredRay> {
-    // First, traverse all object and find the nearest object that the ray meets.
-    let mut hit_info = None;
-    for (i, obj) in entities.iter().enumerate() {
-        if let Some(record) = obj.geometry.hit(ray, (min_t, max_t)) {
-            max_t = record.t;
-            hit_info = Some((i, record));
-        }
-    }
-    let (i, record) = hit_info?;
-    // Next, compute scattering on the surface.
-    Some(entities[i].material.scatter(ray, &record))
+    t_range: (f64, f64),
+    sampler: &mut dyn Sampler,
+) -> Option<Scattering> {
+    // First, find the nearest object that the ray meets.
+    let (i, record) = world.hit(ray, t_range)?;
+    // Next, compute emission and scattering on the surface.
+    let material = &world.entities[i].material;
+    Some(Scattering {
+        emitted: material.emitted(ray, &record),
+        scattered: material.sample(ray, &record, sampler),
+    })
 }
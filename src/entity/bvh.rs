@@ -0,0 +1,359 @@
+//! Implement a bounding volume hierarchy [`Bvh`], which accelerates ray queries against a
+//! large number of primitives.
+//!
+//! The hierarchy is built with the surface area heuristic (SAH) over binned centroids, and
+//! large subtrees are built in parallel with [`rayon::join`].
+
+use super::geometry::{Aabb, GeometryHit};
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// A bounding volume hierarchy over a list of primitives.
+///
+/// The hierarchy only stores the bounding boxes and indices of the primitives, so it can be
+/// shared by any container of primitives (entities in a world, triangles in a mesh, ...).
+pub struct Bvh {
+    /// The flattened nodes in depth-first order. The root node is at index 0.
+    nodes: Vec<BvhNode>,
+    /// The primitive indices, ordered such that each leaf covers a contiguous range.
+    indices: Vec<usize>,
+}
+
+/// A node in the flattened hierarchy.
+struct BvhNode {
+    /// The bounding box of all primitives under this node.
+    bbox: Aabb,
+    /// Whether the node is a leaf or an interior node.
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy)]
+enum NodeKind {
+    /// A leaf node, covering `indices[start..start + count]`.
+    Leaf { start: usize, count: usize },
+    /// An interior node. The first child immediately follows this node, and the second child
+    /// is located at `second`. `axis` is the axis along which the children are split.
+    Interior { second: usize, axis: usize },
+}
+
+/// A node of the intermediate tree produced during construction.
+enum BuildNode {
+    Leaf {
+        bbox: Aabb,
+        start: usize,
+        count: usize,
+    },
+    Interior {
+        bbox: Aabb,
+        axis: usize,
+        children: Box<[BuildNode; 2]>,
+    },
+}
+
+/// A primitive seen by the builder.
+#[derive(Clone, Copy)]
+struct Primitive {
+    index: usize,
+    bbox: Aabb,
+    centroid: na::Point3<f64>,
+}
+
+impl Bvh {
+    /// Number of bins used to evaluate candidate splits along each axis.
+    const BINS: usize = 16;
+    /// Leaves are forced to split when they contain more primitives than this.
+    const MAX_LEAF_SIZE: usize = 4;
+    /// Subtrees with more primitives than this are built in parallel.
+    const PARALLEL_THRESHOLD: usize = 4096;
+    /// Cost of traversing an interior node, relative to intersecting a primitive.
+    const TRAVERSAL_COST: f64 = 0.125;
+}
+
+impl Bvh {
+    /// Build a hierarchy over primitives with the given bounding boxes.
+    ///
+    /// The primitive `i` is referred to by index `i` in queries.
+    pub fn new(boxes: &[Aabb]) -> Self {
+        let mut primitives: Vec<Primitive> = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, bbox)| Primitive {
+                index,
+                bbox: *bbox,
+                centroid: bbox.centroid(),
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * primitives.len());
+        if !primitives.is_empty() {
+            let root = Self::build(&mut primitives, 0);
+            Self::flatten(root, &mut nodes);
+        }
+        Self {
+            nodes,
+            indices: primitives.iter().map(|p| p.index).collect(),
+        }
+    }
+
+    /// Recursively build the tree over `primitives`, which starts at `offset` of the whole list.
+    /// The slice is reordered in place such that each leaf covers a contiguous range.
+    fn build(primitives: &mut [Primitive], offset: usize) -> BuildNode {
+        let count = primitives.len();
+        let bbox = primitives
+            .iter()
+            .fold(Aabb::empty(), |bbox, p| bbox.union(&p.bbox));
+        let leaf = BuildNode::Leaf {
+            bbox,
+            start: offset,
+            count,
+        };
+        if count == 1 {
+            return leaf;
+        }
+
+        let centroid_bounds = Aabb::from_points(primitives.iter().map(|p| &p.centroid));
+        let (axis, mid) = match Self::find_split(primitives, &bbox, &centroid_bounds) {
+            Some((axis, split, cost)) => {
+                if count <= Self::MAX_LEAF_SIZE && cost >= count as f64 {
+                    return leaf;
+                }
+                let bin = |p: &Primitive| Self::bin_index(p, axis, &centroid_bounds);
+                (axis, partition(primitives, |p| bin(p) < split))
+            }
+            None => {
+                // All centroids coincide, so no split can separate them.
+                if count <= Self::MAX_LEAF_SIZE {
+                    return leaf;
+                }
+                (centroid_bounds.longest_axis(), count / 2)
+            }
+        };
+
+        let (left, right) = primitives.split_at_mut(mid);
+        let children = if count > Self::PARALLEL_THRESHOLD {
+            rayon::join(
+                || Self::build(left, offset),
+                || Self::build(right, offset + mid),
+            )
+        } else {
+            (Self::build(left, offset), Self::build(right, offset + mid))
+        };
+        BuildNode::Interior {
+            bbox,
+            axis,
+            children: Box::new([children.0, children.1]),
+        }
+    }
+
+    /// Find the best split with the surface area heuristic.
+    ///
+    /// Returns the axis, the number of bins on the left side and the relative cost of the
+    /// split, or `None` if the centroids cannot be separated along any axis.
+    fn find_split(
+        primitives: &[Primitive],
+        bbox: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, usize, f64)> {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for axis in 0..3 {
+            if centroid_bounds.extent()[axis] <= 0. {
+                continue;
+            }
+
+            let mut bins = [(0usize, Aabb::empty()); Self::BINS];
+            for p in primitives {
+                let bin = &mut bins[Self::bin_index(p, axis, centroid_bounds)];
+                bin.0 += 1;
+                bin.1 = bin.1.union(&p.bbox);
+            }
+
+            // Sweep from the right to collect the cost of the right side of each split.
+            let mut right_cost = [0.; Self::BINS];
+            let (mut count, mut bounds) = (0, Aabb::empty());
+            for i in (1..Self::BINS).rev() {
+                count += bins[i].0;
+                bounds = bounds.union(&bins[i].1);
+                right_cost[i] = count as f64 * bounds.surface_area();
+            }
+
+            // Sweep from the left and combine with the right side.
+            let (mut count, mut bounds) = (0, Aabb::empty());
+            for split in 1..Self::BINS {
+                count += bins[split - 1].0;
+                bounds = bounds.union(&bins[split - 1].1);
+                let cost = Self::TRAVERSAL_COST
+                    + (count as f64 * bounds.surface_area() + right_cost[split])
+                        / bbox.surface_area();
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((axis, split, cost));
+                }
+            }
+        }
+        best
+    }
+
+    /// Compute the bin that the centroid of the primitive falls in.
+    fn bin_index(primitive: &Primitive, axis: usize, centroid_bounds: &Aabb) -> usize {
+        let offset = primitive.centroid[axis] - centroid_bounds.min[axis];
+        let relative = offset / centroid_bounds.extent()[axis];
+        ((relative * Self::BINS as f64) as usize).min(Self::BINS - 1)
+    }
+
+    /// Flatten the tree into `nodes` in depth-first order.
+    fn flatten(node: BuildNode, nodes: &mut Vec<BvhNode>) {
+        match node {
+            BuildNode::Leaf { bbox, start, count } => nodes.push(BvhNode {
+                bbox,
+                kind: NodeKind::Leaf { start, count },
+            }),
+            BuildNode::Interior {
+                bbox,
+                axis,
+                children,
+            } => {
+                let current = nodes.len();
+                nodes.push(BvhNode {
+                    bbox,
+                    kind: NodeKind::Interior { second: 0, axis },
+                });
+                let [first, second] = *children;
+                Self::flatten(first, nodes);
+                nodes[current].kind = NodeKind::Interior {
+                    second: nodes.len(),
+                    axis,
+                };
+                Self::flatten(second, nodes);
+            }
+        }
+    }
+}
+
+impl Bvh {
+    /// The bounding box of all primitives in the hierarchy.
+    pub fn bounding_box(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::empty(), |node| node.bbox)
+    }
+
+    /// Find the nearest primitive hit by the ray within the specified range.
+    ///
+    /// `hit` computes the intersection of the ray with the primitive of the given index, within
+    /// the given range. Returns the index of the nearest primitive together with its hit record.
+    pub fn hit(
+        &self,
+        ray: &Ray,
+        (min_t, mut max_t): (f64, f64),
+        mut hit: impl FnMut(usize, (f64, f64)) -> Option<GeometryHit>,
+    ) -> Option<(usize, GeometryHit)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_direction = ray.direction.map(|d| 1. / d);
+        let mut nearest = None;
+        self.traverse(
+            0,
+            ray,
+            &inv_direction,
+            (min_t, &mut max_t),
+            &mut hit,
+            &mut nearest,
+        );
+        nearest
+    }
+
+    /// Traverse the subtree rooted at `node`, visiting the nearer child first so that
+    /// `max_t` shrinks as early as possible.
+    fn traverse<F>(
+        &self,
+        node: usize,
+        ray: &Ray,
+        inv_direction: &na::Vector3<f64>,
+        (min_t, max_t): (f64, &mut f64),
+        hit: &mut F,
+        nearest: &mut Option<(usize, GeometryHit)>,
+    ) where
+        F: FnMut(usize, (f64, f64)) -> Option<GeometryHit>,
+    {
+        let BvhNode { bbox, kind } = &self.nodes[node];
+        if !bbox.hit(ray, inv_direction, (min_t, *max_t)) {
+            return;
+        }
+        match *kind {
+            NodeKind::Leaf { start, count } => {
+                for &i in &self.indices[start..start + count] {
+                    if let Some(record) = hit(i, (min_t, *max_t)) {
+                        *max_t = record.t;
+                        *nearest = Some((i, record));
+                    }
+                }
+            }
+            NodeKind::Interior { second, axis } => {
+                let (near, far) = if inv_direction[axis] < 0. {
+                    (second, node + 1)
+                } else {
+                    (node + 1, second)
+                };
+                self.traverse(near, ray, inv_direction, (min_t, max_t), hit, nearest);
+                self.traverse(far, ray, inv_direction, (min_t, max_t), hit, nearest);
+            }
+        }
+    }
+}
+
+impl Bvh {
+    /// Judge whether the ray hits any primitive within the specified range.
+    ///
+    /// `hit` judges whether the ray hits the primitive of the given index within the given
+    /// range. Unlike [`Bvh::hit`], the traversal stops at the first primitive hit, in any order.
+    pub fn occluded(
+        &self,
+        ray: &Ray,
+        t_range: (f64, f64),
+        mut hit: impl FnMut(usize, (f64, f64)) -> bool,
+    ) -> bool {
+        if self.nodes.is_empty() {
+            return false;
+        }
+        let inv_direction = ray.direction.map(|d| 1. / d);
+        self.traverse_any(0, ray, &inv_direction, t_range, &mut hit)
+    }
+
+    /// Traverse the subtree rooted at `node` until any primitive is hit.
+    fn traverse_any<F>(
+        &self,
+        node: usize,
+        ray: &Ray,
+        inv_direction: &na::Vector3<f64>,
+        t_range: (f64, f64),
+        hit: &mut F,
+    ) -> bool
+    where
+        F: FnMut(usize, (f64, f64)) -> bool,
+    {
+        let BvhNode { bbox, kind } = &self.nodes[node];
+        if !bbox.hit(ray, inv_direction, t_range) {
+            return false;
+        }
+        match *kind {
+            NodeKind::Leaf { start, count } => self.indices[start..start + count]
+                .iter()
+                .any(|&i| hit(i, t_range)),
+            NodeKind::Interior { second, .. } => {
+                self.traverse_any(node + 1, ray, inv_direction, t_range, hit)
+                    || self.traverse_any(second, ray, inv_direction, t_range, hit)
+            }
+        }
+    }
+}
+
+/// Reorder the slice such that all elements satisfying the predicate precede the others.
+/// Returns the number of elements satisfying the predicate.
+fn partition<T>(slice: &mut [T], predicate: impl Fn(&T) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..slice.len() {
+        if predicate(&slice[i]) {
+            slice.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
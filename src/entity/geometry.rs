@@ -1,41 +1,117 @@
 //! This module defines the [`Geometry`] trait, which should be implemented for
 //! a geometry shape.
 
+/// Implement [`Aabb`], the bounding box of geometry shapes.
+mod aabb;
+/// Implement [`Disk`] as a [`Geometry`].
+mod disk;
+/// Implement [`TriangleMesh`] as a [`Geometry`].
+mod mesh;
+/// Implement [`Quad`] as a [`Geometry`].
+mod quad;
 /// Implement [`Sphere`] as a [`Geometry`].
 mod sphere;
+/// Implement [`Triangle`] as a [`Geometry`].
+mod triangle;
 
 /// Re-export the implemented geometry shapes.
-pub use self::sphere::Sphere;
+pub use self::{
+    aabb::Aabb, disk::Disk, mesh::TriangleMesh, quad::Quad, sphere::Sphere, triangle::Triangle,
+};
 
 use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
 use nalgebra as na;
 
 /// Defines the information of the intersection point when a ray hits an visible object.
+#[derive(Clone)]
 pub struct GeometryHit {
     /// The intersection point of the ray and the entity.
     pub point: na::Point3<f64>,
+    /// The shading normal at the intersection point, which materials should use.
+    /// It may differ from the geometric normal, e.g. when interpolated from vertex normals, but
+    /// always lies on the same side of the surface as the geometric normal.
+    pub normal: na::UnitVector3<f64>,
     /// The normal vector of the entity surface at the intersection point.
     /// The orientation of the normal vector depends on the incoming ray direction.
-    pub normal: na::UnitVector3<f64>,
+    pub geometric_normal: na::UnitVector3<f64>,
     /// The orientation of the normal vector.
     pub exterior: bool,
     /// The parameter `t` of the intersection point on the ray.
     pub t: f64,
+    /// The texture coordinates of the intersection point.
+    /// Geometries without a parameterization leave it at the origin.
+    pub uv: na::Point2<f64>,
+    /// The partial derivative of the point with respect to `u`.
+    pub dpdu: na::Vector3<f64>,
+    /// The partial derivative of the point with respect to `v`.
+    pub dpdv: na::Vector3<f64>,
+    /// The barycentric coordinates of the intersection point with respect to the three
+    /// vertices, if the geometry is built from triangles.
+    pub barycentric: Option<na::Vector3<f64>>,
 }
 
 impl GeometryHit {
-    /// Create a new [`GeometryHit`] instance from ray, t and normal.
+    /// Create a new [`GeometryHit`] instance from ray, t and the outward geometric normal.
+    ///
+    /// The shading normal is initialized to the geometric normal, and the tangents are
+    /// initialized to an arbitrary orthonormal basis of the tangent plane.
     fn new(ray: &Ray, normal: na::UnitVector3<f64>, t: f64) -> Self {
         let point = ray.at(t);
         let exterior = normal.dot(&ray.direction) < 0.;
         let normal = if exterior { normal } else { -normal };
+        let (dpdu, dpdv) = orthonormal_basis(&normal);
         Self {
             point,
             normal,
+            geometric_normal: normal,
             exterior,
             t,
+            uv: na::Point2::origin(),
+            dpdu,
+            dpdv,
+            barycentric: None,
         }
     }
+
+    /// Attach texture coordinates to the hit record.
+    fn with_uv(mut self, uv: na::Point2<f64>) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    /// Attach the partial derivatives of the point with respect to the texture coordinates.
+    fn with_tangents(mut self, dpdu: na::Vector3<f64>, dpdv: na::Vector3<f64>) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// Attach a shading normal, which is flipped to the side of the geometric normal if needed.
+    fn with_shading_normal(mut self, normal: na::UnitVector3<f64>) -> Self {
+        self.normal = if normal.dot(&self.geometric_normal) < 0. {
+            -normal
+        } else {
+            normal
+        };
+        self
+    }
+
+    /// Attach barycentric coordinates to the hit record.
+    fn with_barycentric(mut self, barycentric: na::Vector3<f64>) -> Self {
+        self.barycentric = Some(barycentric);
+        self
+    }
+}
+
+/// Defines a point sampled on the surface of a geometry, as seen from a reference point.
+pub struct GeometrySample {
+    /// The hit record of the sampled point, as if it were hit by the ray from the reference
+    /// point towards it at `t = 1`.
+    pub hit: GeometryHit,
+    /// The probability density of the sampled point, with respect to solid angle at the
+    /// reference point, or with respect to area for [`Geometry::sample_surface`].
+    pub pdf: f64,
 }
 
 /// A trait that computes the intersection of a ray and a geometry shape.
@@ -44,4 +120,57 @@ pub trait Geometry: Send + Sync {
     ///
     /// Returns `None` if the ray does not hit the geometry within the specified range.    
     fn hit(&self, ray: &Ray, t_range: (f64, f64)) -> Option<GeometryHit>;
+
+    /// Compute an axis-aligned box that bounds the whole geometry.
+    fn bounding_box(&self) -> Aabb;
+
+    /// The area of the surface, over which [`Geometry::sample_surface`] samples uniformly.
+    ///
+    /// Returns 0 if the geometry does not support sampling, which is the default.
+    fn area(&self) -> f64 {
+        0.
+    }
+
+    /// Sample a point on the surface as seen from the reference point, given two uniform random
+    /// numbers in [0, 1). This allows geometries with an emissive material to be sampled as
+    /// lights.
+    ///
+    /// Returns `None` if the geometry does not support sampling, which is the default.
+    fn sample(&self, _reference: &na::Point3<f64>, _u: (f64, f64)) -> Option<GeometrySample> {
+        None
+    }
+
+    /// The probability density (with respect to solid angle) that [`Geometry::sample`] returns
+    /// the point hit by the ray from the reference point along the given direction.
+    fn pdf(&self, _reference: &na::Point3<f64>, _direction: &na::Vector3<f64>) -> f64 {
+        0.
+    }
+
+    /// Sample a point uniformly over the surface, given two uniform random numbers in [0, 1).
+    /// This allows geometries with an emissive material to emit light from their surface.
+    ///
+    /// The hit record is oriented as if the point were hit from the exterior at `t = 1`, and
+    /// the density is with respect to area. Returns `None` if the geometry does not support
+    /// sampling, which is the default.
+    fn sample_surface(&self, _u: (f64, f64)) -> Option<GeometrySample> {
+        None
+    }
+}
+
+/// Create a ray that hits the point from the side the outward normal points to, at `t = 1`.
+fn exterior_ray(point: &na::Point3<f64>, normal: &na::Vector3<f64>) -> Ray {
+    Ray::new(point + normal, -normal)
+}
+
+/// Convert a probability density with respect to area at the hit point into the density with
+/// respect to solid angle at the origin of the ray.
+fn solid_angle_pdf(ray: &Ray, hit: &GeometryHit, area_pdf: f64) -> f64 {
+    let offset = hit.point - ray.origin;
+    let distance_squared = offset.norm_squared();
+    let cosine = hit.geometric_normal.dot(&offset).abs() / distance_squared.sqrt();
+    if cosine > 0. {
+        area_pdf * distance_squared / cosine
+    } else {
+        0.
+    }
 }
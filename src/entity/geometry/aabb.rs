@@ -0,0 +1,113 @@
+//! Implement an axis-aligned bounding box [`Aabb`] in 3D space.
+
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// An axis-aligned bounding box, determined by its minimum and maximum corners.
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    /// The corner with the smallest coordinates.
+    pub min: na::Point3<f64>,
+    /// The corner with the largest coordinates.
+    pub max: na::Point3<f64>,
+}
+
+impl Aabb {
+    /// Create a bounding box from two corners, which need not be sorted.
+    pub fn new(a: na::Point3<f64>, b: na::Point3<f64>) -> Self {
+        Self {
+            min: a.inf(&b),
+            max: a.sup(&b),
+        }
+    }
+
+    /// Create an empty bounding box, which is the identity of [`Aabb::union`].
+    pub fn empty() -> Self {
+        Self {
+            min: na::Point3::from([f64::INFINITY; 3]),
+            max: na::Point3::from([f64::NEG_INFINITY; 3]),
+        }
+    }
+
+    /// Create the smallest bounding box containing all given points.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a na::Point3<f64>>) -> Self {
+        points
+            .into_iter()
+            .fold(Self::empty(), |bbox, point| bbox.grow(point))
+    }
+}
+
+impl Aabb {
+    /// Compute the smallest bounding box containing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    /// Compute the smallest bounding box containing the box and the point.
+    pub fn grow(&self, point: &na::Point3<f64>) -> Self {
+        Self {
+            min: self.min.inf(point),
+            max: self.max.sup(point),
+        }
+    }
+
+    /// Whether the box contains no point at all.
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min[axis] > self.max[axis])
+    }
+
+    /// The center point of the box.
+    pub fn centroid(&self) -> na::Point3<f64> {
+        na::center(&self.min, &self.max)
+    }
+
+    /// The size of the box along each axis.
+    pub fn extent(&self) -> na::Vector3<f64> {
+        self.max - self.min
+    }
+
+    /// The axis (0 for x, 1 for y, 2 for z) along which the box is the longest.
+    pub fn longest_axis(&self) -> usize {
+        self.extent().imax()
+    }
+
+    /// The surface area of the box, or 0 if the box is empty.
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.;
+        }
+        let d = self.extent();
+        2. * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+}
+
+impl Aabb {
+    /// Judge whether the ray (with a specified range) passes through the box.
+    ///
+    /// `inv_direction` should be the component-wise reciprocal of the ray direction, which is
+    /// computed once by the caller since the same ray is tested against many boxes.
+    #[inline]
+    pub fn hit(
+        &self,
+        ray: &Ray,
+        inv_direction: &na::Vector3<f64>,
+        (mut min_t, mut max_t): (f64, f64),
+    ) -> bool {
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - ray.origin[axis]) * inv_direction[axis];
+            let t1 = (self.max[axis] - ray.origin[axis]) * inv_direction[axis];
+            // Note: `f64::min` and `f64::max` ignore NaN, which arises (as 0 * inf) when a ray
+            // parallel to the slab starts exactly on its boundary. The infinite bound is taken
+            // instead, so such rays are rejected, missing at most a grazing hit along a face.
+            min_t = min_t.max(t0.min(t1));
+            max_t = max_t.min(t0.max(t1));
+            if max_t < min_t {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,123 @@
+//! Implement a [`Disk`] in 3D space.
+
+use super::{exterior_ray, solid_angle_pdf, Aabb, Geometry, GeometryHit, GeometrySample};
+use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
+use nalgebra as na;
+
+/// A disk in 3D space which is parameterized by its center, normal and radius.
+///
+/// The exterior side is the one the normal points to. `u` is the angle around the normal
+/// and `v` is the distance to the center, both normalized to [0, 1].
+pub struct Disk {
+    /// The center of the disk in 3D space.
+    pub center: na::Point3<f64>,
+    /// The normal of the disk, pointing to the exterior side.
+    pub normal: na::UnitVector3<f64>,
+    /// The radius of the disk.
+    pub radius: f64,
+}
+
+impl Disk {
+    /// Create a disk in 3D space with given center, normal and radius.
+    pub fn new(center: na::Point3<f64>, normal: na::UnitVector3<f64>, radius: f64) -> Self {
+        Self {
+            center,
+            normal,
+            radius,
+        }
+    }
+}
+
+impl Disk {
+    /// Create the hit record of the ray at `t`, given the polar coordinates of the point.
+    fn record(&self, ray: &Ray, t: f64, (rho, phi): (f64, f64)) -> GeometryHit {
+        use std::f64::consts::TAU;
+
+        let (e1, e2) = orthonormal_basis(&self.normal);
+        let radial = phi.cos() * e1 + phi.sin() * e2;
+        let angular = -phi.sin() * e1 + phi.cos() * e2;
+        GeometryHit::new(ray, self.normal, t)
+            .with_uv(na::point![phi / TAU, rho / self.radius])
+            .with_tangents(TAU * rho * angular, self.radius * radial)
+    }
+}
+
+impl Geometry for Disk {
+    fn hit(&self, ray: &Ray, (min_t, max_t): (f64, f64)) -> Option<GeometryHit> {
+        let denominator = self.normal.dot(&ray.direction);
+        // The ray is parallel to the plane of the disk.
+        if denominator == 0. {
+            return None;
+        }
+        let t = self.normal.dot(&(self.center - ray.origin)) / denominator;
+        if t <= min_t || max_t <= t {
+            return None;
+        }
+
+        let p = ray.at(t) - self.center;
+        let rho = p.norm();
+        if rho > self.radius {
+            return None;
+        }
+        let (e1, e2) = orthonormal_basis(&self.normal);
+        let phi = p
+            .dot(&e2)
+            .atan2(p.dot(&e1))
+            .rem_euclid(std::f64::consts::TAU);
+        Some(self.record(ray, t, (rho, phi)))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // The extent of the disk along each axis is determined by the other two components of
+        // the normal.
+        let n = self.normal.into_inner();
+        let extent = self.radius * n.map(|c| (1. - c * c).max(0.).sqrt());
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn sample(&self, reference: &na::Point3<f64>, u: (f64, f64)) -> Option<GeometrySample> {
+        if self.radius <= 0. {
+            return None;
+        }
+        let (point, (rho, phi)) = self.sample_point(u);
+
+        let ray = Ray::new(*reference, point - reference);
+        let hit = self.record(&ray, 1., (rho, phi));
+        let pdf = solid_angle_pdf(&ray, &hit, 1. / self.area());
+        (pdf > 0.).then_some(GeometrySample { hit, pdf })
+    }
+
+    fn pdf(&self, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        let ray = Ray::new(*reference, *direction);
+        self.hit(&ray, (Ray::T_MIN, f64::INFINITY))
+            .map_or(0., |hit| solid_angle_pdf(&ray, &hit, 1. / self.area()))
+    }
+
+    fn sample_surface(&self, u: (f64, f64)) -> Option<GeometrySample> {
+        if self.radius <= 0. {
+            return None;
+        }
+        let (point, polar) = self.sample_point(u);
+        let ray = exterior_ray(&point, &self.normal);
+        Some(GeometrySample {
+            hit: self.record(&ray, 1., polar),
+            pdf: 1. / self.area(),
+        })
+    }
+}
+
+impl Disk {
+    /// Sample a point uniformly over the disk, and return it with its polar coordinates.
+    fn sample_point(&self, (u0, u1): (f64, f64)) -> (na::Point3<f64>, (f64, f64)) {
+        let rho = self.radius * u0.sqrt();
+        let phi = std::f64::consts::TAU * u1;
+        let (e1, e2) = orthonormal_basis(&self.normal);
+        let point = self.center + rho * (phi.cos() * e1 + phi.sin() * e2);
+        (point, (rho, phi))
+    }
+}
@@ -0,0 +1,234 @@
+//! Implement an indexed [`TriangleMesh`] in 3D space.
+
+use super::{exterior_ray, solid_angle_pdf, triangle, Aabb, Geometry, GeometryHit, GeometrySample};
+use crate::distribution::Distribution1D;
+use crate::entity::Bvh;
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// A triangle mesh, where the triangles refer to a shared list of vertices by index.
+///
+/// Each vertex has a position, and optionally a normal and a texture coordinate (UV).
+/// The mesh builds its own [`Bvh`] over the triangles, so a mesh with many triangles
+/// behaves like a single entity in the world.
+pub struct TriangleMesh {
+    /// The positions of all vertices.
+    positions: Vec<na::Point3<f64>>,
+    /// The normals of all vertices, used to smoothly interpolate the normal across a triangle.
+    normals: Option<Vec<na::UnitVector3<f64>>>,
+    /// The texture coordinates of all vertices.
+    uvs: Option<Vec<na::Point2<f64>>>,
+    /// Vertex indices of all triangles.
+    indices: Vec<[usize; 3]>,
+    /// The bounding volume hierarchy over all triangles.
+    bvh: Bvh,
+    /// The distribution over triangles proportional to their areas, used to sample points
+    /// uniformly over the surface, or `None` if the mesh has no area.
+    areas: Option<Distribution1D>,
+    /// The total surface area of the mesh.
+    area: f64,
+}
+
+impl TriangleMesh {
+    /// Create a triangle mesh with the given vertex positions and triangle indices.
+    ///
+    /// Panics if any index is out of bounds.
+    pub fn new(positions: Vec<na::Point3<f64>>, indices: Vec<[usize; 3]>) -> Self {
+        assert!(
+            indices.iter().flatten().all(|&i| i < positions.len()),
+            "TriangleMesh: vertex index out of bounds."
+        );
+        let boxes: Vec<Aabb> = indices
+            .iter()
+            .map(|&[i, j, k]| Aabb::from_points([&positions[i], &positions[j], &positions[k]]))
+            .collect();
+        let bvh = Bvh::new(&boxes);
+
+        let areas: Vec<f64> = indices
+            .iter()
+            .map(|&[i, j, k]| triangle::area(&[positions[i], positions[j], positions[k]]))
+            .collect();
+        let area = areas.iter().sum();
+        let areas = (area > 0.).then(|| Distribution1D::new(areas));
+        Self {
+            positions,
+            normals: None,
+            uvs: None,
+            indices,
+            bvh,
+            areas,
+            area,
+        }
+    }
+
+    /// Set per-vertex normals.
+    ///
+    /// Panics if the number of normals differs from the number of vertices.
+    pub fn with_normals(mut self, normals: Vec<na::UnitVector3<f64>>) -> Self {
+        assert_eq!(
+            normals.len(),
+            self.positions.len(),
+            "TriangleMesh: the number of normals should equal the number of vertices."
+        );
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Set per-vertex texture coordinates.
+    ///
+    /// Panics if the number of texture coordinates differs from the number of vertices.
+    pub fn with_uvs(mut self, uvs: Vec<na::Point2<f64>>) -> Self {
+        assert_eq!(
+            uvs.len(),
+            self.positions.len(),
+            "TriangleMesh: the number of UVs should equal the number of vertices."
+        );
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl TriangleMesh {
+    /// Obtain the vertex positions.
+    pub fn positions(&self) -> &[na::Point3<f64>] {
+        &self.positions
+    }
+
+    /// Obtain the vertex normals, if any.
+    pub fn normals(&self) -> Option<&[na::UnitVector3<f64>]> {
+        self.normals.as_deref()
+    }
+
+    /// Obtain the vertex texture coordinates, if any.
+    pub fn uvs(&self) -> Option<&[na::Point2<f64>]> {
+        self.uvs.as_deref()
+    }
+
+    /// Obtain the vertex indices of all triangles.
+    pub fn indices(&self) -> &[[usize; 3]] {
+        &self.indices
+    }
+
+    /// The three vertex positions of a triangle of the mesh.
+    fn vertices(&self, index: usize) -> [na::Point3<f64>; 3] {
+        self.indices[index].map(|i| self.positions[i])
+    }
+
+    /// Compute the intersection of the ray and a single triangle of the mesh.
+    fn hit_triangle(&self, index: usize, ray: &Ray, t_range: (f64, f64)) -> Option<GeometryHit> {
+        let (t, b1, b2) = triangle::intersect(ray, &self.vertices(index), t_range)?;
+        Some(self.record(index, ray, t, (b1, b2)))
+    }
+
+    /// Create the hit record of the ray at `t` on a single triangle of the mesh, given the
+    /// barycentric coordinates `(b1, b2)`.
+    fn record(&self, index: usize, ray: &Ray, t: f64, (b1, b2): (f64, f64)) -> GeometryHit {
+        let [i, j, k] = self.indices[index];
+        let vertices = self.vertices(index);
+        let barycentric = na::vector![1. - b1 - b2, b1, b2];
+
+        let [p0, p1, p2] = &vertices;
+        let geometric = na::UnitVector3::new_normalize((p1 - p0).cross(&(p2 - p0)));
+        let mut record = GeometryHit::new(ray, geometric, t).with_barycentric(barycentric);
+
+        // Without texture coordinates, fall back to the parameterization of a single triangle.
+        match &self.uvs {
+            Some(uvs) => {
+                let [uv0, uv1, uv2] = [uvs[i], uvs[j], uvs[k]];
+                record = record.with_uv(na::Point2::from(
+                    barycentric[0] * uv0.coords
+                        + barycentric[1] * uv1.coords
+                        + barycentric[2] * uv2.coords,
+                ));
+                // Solve the tangents from the differences of positions and UVs along two edges.
+                let (duv1, duv2) = (uv1 - uv0, uv2 - uv0);
+                let det = duv1.x * duv2.y - duv1.y * duv2.x;
+                if det.abs() > f64::EPSILON {
+                    let (dp1, dp2) = (p1 - p0, p2 - p0);
+                    let dpdu = (duv2.y * dp1 - duv1.y * dp2) / det;
+                    let dpdv = (duv1.x * dp2 - duv2.x * dp1) / det;
+                    record = record.with_tangents(dpdu, dpdv);
+                }
+            }
+            None => {
+                record = record
+                    .with_uv(na::point![b1, b2])
+                    .with_tangents(p1 - p0, p2 - p0);
+            }
+        }
+
+        if let Some(normals) = &self.normals {
+            let n = barycentric[0] * *normals[i]
+                + barycentric[1] * *normals[j]
+                + barycentric[2] * *normals[k];
+            if let Some(n) = na::UnitVector3::try_new(n, f64::EPSILON) {
+                record = record.with_shading_normal(n);
+            }
+        }
+        record
+    }
+}
+
+impl Geometry for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_range: (f64, f64)) -> Option<GeometryHit> {
+        self.bvh
+            .hit(ray, t_range, |i, t_range| {
+                self.hit_triangle(i, ray, t_range)
+            })
+            .map(|(_, record)| record)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+
+    fn area(&self) -> f64 {
+        self.area
+    }
+
+    fn sample(&self, reference: &na::Point3<f64>, u: (f64, f64)) -> Option<GeometrySample> {
+        let (index, (b1, b2), point) = self.sample_point(u)?;
+
+        let ray = Ray::new(*reference, point - reference);
+        let hit = self.record(index, &ray, 1., (b1, b2));
+        let pdf = solid_angle_pdf(&ray, &hit, 1. / self.area);
+        (pdf > 0.).then_some(GeometrySample { hit, pdf })
+    }
+
+    fn pdf(&self, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        if self.area <= 0. {
+            return 0.;
+        }
+        let ray = Ray::new(*reference, *direction);
+        self.hit(&ray, (Ray::T_MIN, f64::INFINITY))
+            .map_or(0., |hit| solid_angle_pdf(&ray, &hit, 1. / self.area))
+    }
+
+    fn sample_surface(&self, u: (f64, f64)) -> Option<GeometrySample> {
+        let (index, barycentric, point) = self.sample_point(u)?;
+        let [p0, p1, p2] = self.vertices(index);
+        let ray = exterior_ray(&point, &(p1 - p0).cross(&(p2 - p0)).normalize());
+        Some(GeometrySample {
+            hit: self.record(index, &ray, 1., barycentric),
+            pdf: 1. / self.area,
+        })
+    }
+}
+
+impl TriangleMesh {
+    /// Sample a point uniformly over the area of the mesh.
+    ///
+    /// Returns the index of the triangle, the barycentric coordinates and the point, or `None`
+    /// if the mesh has no area.
+    fn sample_point(&self, (u0, u1): (f64, f64)) -> Option<(usize, (f64, f64), na::Point3<f64>)> {
+        // Choose a triangle proportional to its area, and reuse the position of `u0` within
+        // the interval of the triangle to sample a point on it.
+        let areas = self.areas.as_ref()?;
+        let (x, _, index) = areas.sample(u0);
+        let u0 = x * areas.len() as f64 - index as f64;
+        let (b1, b2) = triangle::sample_barycentric((u0.clamp(0., 1.), u1));
+        let [p0, p1, p2] = self.vertices(index);
+        let point = p0 + b1 * (p1 - p0) + b2 * (p2 - p0);
+        Some((index, (b1, b2), point))
+    }
+}
@@ -0,0 +1,107 @@
+//! Implement a [`Quad`] in 3D space.
+
+use super::{exterior_ray, solid_angle_pdf, Aabb, Geometry, GeometryHit, GeometrySample};
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// A parallelogram in 3D space which is parameterized by a corner and two edges from it.
+///
+/// The exterior side is the one the cross product `u × v` points to. The texture coordinates
+/// go from (0, 0) at the corner to (1, 0) along `u` and (0, 1) along `v`.
+pub struct Quad {
+    /// The corner of the quad.
+    pub corner: na::Point3<f64>,
+    /// The first edge, starting from the corner.
+    pub u: na::Vector3<f64>,
+    /// The second edge, starting from the corner.
+    pub v: na::Vector3<f64>,
+}
+
+impl Quad {
+    /// Create a quad in 3D space with the given corner and two edges from it.
+    pub fn new(corner: na::Point3<f64>, u: na::Vector3<f64>, v: na::Vector3<f64>) -> Self {
+        Self { corner, u, v }
+    }
+}
+
+impl Quad {
+    /// Create the hit record of the ray at `t`, given the coordinates `(a, b)` of the point
+    /// along the two edges.
+    fn record(&self, ray: &Ray, t: f64, (a, b): (f64, f64)) -> GeometryHit {
+        let normal = na::UnitVector3::new_normalize(self.u.cross(&self.v));
+        GeometryHit::new(ray, normal, t)
+            .with_uv(na::point![a, b])
+            .with_tangents(self.u, self.v)
+    }
+}
+
+impl Geometry for Quad {
+    fn hit(&self, ray: &Ray, (min_t, max_t): (f64, f64)) -> Option<GeometryHit> {
+        let n = self.u.cross(&self.v);
+        let denominator = n.dot(&ray.direction);
+        // The ray is parallel to the plane of the quad, or the quad is degenerate.
+        if denominator == 0. {
+            return None;
+        }
+        let t = n.dot(&(self.corner - ray.origin)) / denominator;
+        if t <= min_t || max_t <= t {
+            return None;
+        }
+
+        // Express the hit point in the coordinates along the two edges.
+        let w = n / n.norm_squared();
+        let p = ray.at(t) - self.corner;
+        let a = w.dot(&p.cross(&self.v));
+        let b = w.dot(&self.u.cross(&p));
+        if !(0. ..=1.).contains(&a) || !(0. ..=1.).contains(&b) {
+            return None;
+        }
+        Some(self.record(ray, t, (a, b)))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let corners = [
+            self.corner,
+            self.corner + self.u,
+            self.corner + self.v,
+            self.corner + self.u + self.v,
+        ];
+        Aabb::from_points(&corners)
+    }
+
+    fn area(&self) -> f64 {
+        self.u.cross(&self.v).norm()
+    }
+
+    fn sample(&self, reference: &na::Point3<f64>, (u0, u1): (f64, f64)) -> Option<GeometrySample> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let point = self.corner + u0 * self.u + u1 * self.v;
+
+        let ray = Ray::new(*reference, point - reference);
+        let hit = self.record(&ray, 1., (u0, u1));
+        let pdf = solid_angle_pdf(&ray, &hit, 1. / area);
+        (pdf > 0.).then_some(GeometrySample { hit, pdf })
+    }
+
+    fn pdf(&self, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        let ray = Ray::new(*reference, *direction);
+        self.hit(&ray, (Ray::T_MIN, f64::INFINITY))
+            .map_or(0., |hit| solid_angle_pdf(&ray, &hit, 1. / self.area()))
+    }
+
+    fn sample_surface(&self, (u0, u1): (f64, f64)) -> Option<GeometrySample> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let point = self.corner + u0 * self.u + u1 * self.v;
+        let ray = exterior_ray(&point, &self.u.cross(&self.v).normalize());
+        Some(GeometrySample {
+            hit: self.record(&ray, 1., (u0, u1)),
+            pdf: 1. / area,
+        })
+    }
+}
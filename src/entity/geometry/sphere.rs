@@ -1,7 +1,8 @@
 //! Implement a [`Sphere`] in 3D space.
 
-use super::{Geometry, GeometryHit};
+use super::{exterior_ray, solid_angle_pdf, Aabb, Geometry, GeometryHit, GeometrySample};
 use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
 use nalgebra as na;
 
 /// A sphere in 3D space which is parameterized by its radius and center.
@@ -19,6 +20,41 @@ impl Sphere {
     }
 }
 
+impl Sphere {
+    /// Compute the spherical texture coordinates and their tangents at a point on the sphere,
+    /// given the outward unit normal at the point.
+    ///
+    /// `u` is the angle around the Y axis starting from -X, and `v` is the angle from -Y to +Y,
+    /// both normalized to [0, 1].
+    fn parameterize(
+        &self,
+        normal: &na::UnitVector3<f64>,
+    ) -> (na::Point2<f64>, na::Vector3<f64>, na::Vector3<f64>) {
+        use std::f64::consts::{PI, TAU};
+
+        let theta = (-normal.y).clamp(-1., 1.).acos();
+        let phi = f64::atan2(-normal.z, normal.x) + PI;
+        let uv = na::point![phi / TAU, theta / PI];
+
+        // The local position relative to the center, and its distance to the Y axis.
+        let p = self.radius * normal.into_inner();
+        let rho = p.x.hypot(p.z).max(f64::MIN_POSITIVE);
+        let dpdu = TAU * na::vector![p.z, 0., -p.x];
+        let dpdv = PI * na::vector![-p.x * p.y / rho, rho, -p.y * p.z / rho];
+        (uv, dpdu, dpdv)
+    }
+}
+
+impl Sphere {
+    /// Create the hit record of the ray at `t`, given the outward unit normal there.
+    fn record(&self, ray: &Ray, normal: na::UnitVector3<f64>, t: f64) -> GeometryHit {
+        let (uv, dpdu, dpdv) = self.parameterize(&normal);
+        GeometryHit::new(ray, normal, t)
+            .with_uv(uv)
+            .with_tangents(dpdu, dpdv)
+    }
+}
+
 impl Geometry for Sphere {
     fn hit(&self, ray: &Ray, (min_t, max_t): (f64, f64)) -> Option<GeometryHit> {
         let oc = self.center - ray.origin;
@@ -40,8 +76,111 @@ impl Geometry for Sphere {
             }
         }
 
-        let point = ray.at(t);
+        let normal = na::UnitVector3::new_normalize(ray.at(t) - self.center);
+        Some(self.record(ray, normal, t))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let r = na::Vector3::repeat(self.radius.abs());
+        Aabb::new(self.center - r, self.center + r)
+    }
+
+    fn area(&self) -> f64 {
+        4. * std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn sample(&self, reference: &na::Point3<f64>, u: (f64, f64)) -> Option<GeometrySample> {
+        match self.cone(reference) {
+            Some(one_minus_cos_max) => self.sample_cone(reference, one_minus_cos_max, u),
+            None => self.sample_area(reference, u),
+        }
+    }
+
+    fn pdf(&self, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        let ray = Ray::new(*reference, *direction);
+        let Some(hit) = self.hit(&ray, (Ray::T_MIN, f64::INFINITY)) else {
+            return 0.;
+        };
+        match self.cone(reference) {
+            Some(one_minus_cos_max) => 1. / (std::f64::consts::TAU * one_minus_cos_max),
+            None => solid_angle_pdf(&ray, &hit, 1. / self.area()),
+        }
+    }
+
+    fn sample_surface(&self, u: (f64, f64)) -> Option<GeometrySample> {
+        let (point, normal) = self.sample_point(u);
+        let ray = exterior_ray(&point, &normal);
+        Some(GeometrySample {
+            hit: self.record(&ray, normal, 1.),
+            pdf: 1. / self.area(),
+        })
+    }
+}
+
+impl Sphere {
+    /// Compute `1 - cos(theta)`, where `theta` is the half angle of the cone subtended by the
+    /// sphere at the reference point, or `None` if the point is inside the sphere.
+    fn cone(&self, reference: &na::Point3<f64>) -> Option<f64> {
+        let sin2_max = self.radius.powi(2) / (self.center - reference).norm_squared();
+        if sin2_max >= 1. {
+            return None;
+        }
+        // Equivalent to `1 - sqrt(1 - sin2_max)`, but accurate for small and distant spheres.
+        Some(sin2_max / (1. + (1. - sin2_max).sqrt()))
+    }
+
+    /// Sample a direction uniformly within the cone subtended by the sphere, and find the point
+    /// where it first meets the sphere.
+    fn sample_cone(
+        &self,
+        reference: &na::Point3<f64>,
+        one_minus_cos_max: f64,
+        (u0, u1): (f64, f64),
+    ) -> Option<GeometrySample> {
+        let (axis, distance) = na::Unit::try_new_and_get(self.center - reference, 0.)?;
+        let one_minus_cos = u0 * one_minus_cos_max;
+        let cos_theta = 1. - one_minus_cos;
+        let sin2_theta = one_minus_cos * (2. - one_minus_cos);
+        let phi = std::f64::consts::TAU * u1;
+
+        let (t1, t2) = orthonormal_basis(&axis);
+        let direction =
+            cos_theta * axis.into_inner() + sin2_theta.sqrt() * (phi.cos() * t1 + phi.sin() * t2);
+        // The distance to the nearer intersection of the direction and the sphere.
+        let offset = distance * cos_theta
+            - (self.radius.powi(2) - distance * distance * sin2_theta)
+                .max(0.)
+                .sqrt();
+        let point = reference + offset * direction;
+
         let normal = na::UnitVector3::new_normalize(point - self.center);
-        Some(GeometryHit::new(ray, normal, t))
+        let ray = Ray::new(*reference, point - reference);
+        Some(GeometrySample {
+            hit: self.record(&ray, normal, 1.),
+            pdf: 1. / (std::f64::consts::TAU * one_minus_cos_max),
+        })
+    }
+
+    /// Sample a point uniformly over the area of the sphere.
+    fn sample_area(&self, reference: &na::Point3<f64>, u: (f64, f64)) -> Option<GeometrySample> {
+        let (point, normal) = self.sample_point(u);
+
+        let ray = Ray::new(*reference, point - reference);
+        let hit = self.record(&ray, normal, 1.);
+        let pdf = solid_angle_pdf(&ray, &hit, 1. / self.area());
+        (pdf > 0.).then_some(GeometrySample { hit, pdf })
+    }
+
+    /// Sample a point uniformly over the area of the sphere, and return it with the outward
+    /// normal there.
+    fn sample_point(&self, (u0, u1): (f64, f64)) -> (na::Point3<f64>, na::UnitVector3<f64>) {
+        let z = 1. - 2. * u0;
+        let r = (1. - z * z).max(0.).sqrt();
+        let phi = std::f64::consts::TAU * u1;
+        let normal = na::UnitVector3::new_unchecked(na::vector![r * phi.cos(), r * phi.sin(), z]);
+        (
+            self.center + self.radius.abs() * normal.into_inner(),
+            normal,
+        )
     }
 }
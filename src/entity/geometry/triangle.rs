@@ -0,0 +1,138 @@
+//! Implement a [`Triangle`] in 3D space.
+
+use super::{exterior_ray, solid_angle_pdf, Aabb, Geometry, GeometryHit, GeometrySample};
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// A single triangle in 3D space which is parameterized by its three vertices.
+///
+/// The orientation of the vertices (counter-clockwise when viewed from outside) determines
+/// which side of the triangle is the exterior. The texture coordinates of the three vertices
+/// are (0, 0), (1, 0) and (0, 1) respectively.
+pub struct Triangle {
+    /// The three vertices of the triangle.
+    pub vertices: [na::Point3<f64>; 3],
+}
+
+impl Triangle {
+    /// Create a triangle in 3D space with the given vertices.
+    pub fn new(a: na::Point3<f64>, b: na::Point3<f64>, c: na::Point3<f64>) -> Self {
+        Self {
+            vertices: [a, b, c],
+        }
+    }
+}
+
+impl Triangle {
+    /// Create the hit record of the ray at `t`, given the barycentric coordinates `(b1, b2)`.
+    fn record(&self, ray: &Ray, t: f64, (b1, b2): (f64, f64)) -> GeometryHit {
+        let [p0, p1, p2] = &self.vertices;
+        let normal = na::UnitVector3::new_normalize((p1 - p0).cross(&(p2 - p0)));
+        GeometryHit::new(ray, normal, t)
+            .with_uv(na::point![b1, b2])
+            .with_tangents(p1 - p0, p2 - p0)
+            .with_barycentric(na::vector![1. - b1 - b2, b1, b2])
+    }
+}
+
+impl Geometry for Triangle {
+    fn hit(&self, ray: &Ray, t_range: (f64, f64)) -> Option<GeometryHit> {
+        let (t, b1, b2) = intersect(ray, &self.vertices, t_range)?;
+        Some(self.record(ray, t, (b1, b2)))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(&self.vertices)
+    }
+
+    fn area(&self) -> f64 {
+        area(&self.vertices)
+    }
+
+    fn sample(&self, reference: &na::Point3<f64>, u: (f64, f64)) -> Option<GeometrySample> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let (b1, b2) = sample_barycentric(u);
+        let [p0, p1, p2] = &self.vertices;
+        let point = p0 + b1 * (p1 - p0) + b2 * (p2 - p0);
+
+        let ray = Ray::new(*reference, point - reference);
+        let hit = self.record(&ray, 1., (b1, b2));
+        let pdf = solid_angle_pdf(&ray, &hit, 1. / area);
+        (pdf > 0.).then_some(GeometrySample { hit, pdf })
+    }
+
+    fn pdf(&self, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        let ray = Ray::new(*reference, *direction);
+        self.hit(&ray, (Ray::T_MIN, f64::INFINITY))
+            .map_or(0., |hit| solid_angle_pdf(&ray, &hit, 1. / self.area()))
+    }
+
+    fn sample_surface(&self, u: (f64, f64)) -> Option<GeometrySample> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let (b1, b2) = sample_barycentric(u);
+        let [p0, p1, p2] = &self.vertices;
+        let point = p0 + b1 * (p1 - p0) + b2 * (p2 - p0);
+        let ray = exterior_ray(&point, &(p1 - p0).cross(&(p2 - p0)).normalize());
+        Some(GeometrySample {
+            hit: self.record(&ray, 1., (b1, b2)),
+            pdf: 1. / area,
+        })
+    }
+}
+
+/// Compute the area of a triangle.
+pub(super) fn area([p0, p1, p2]: &[na::Point3<f64>; 3]) -> f64 {
+    0.5 * (p1 - p0).cross(&(p2 - p0)).norm()
+}
+
+/// Sample the barycentric coordinates `(b1, b2)` of a point uniformly distributed over a
+/// triangle, given two uniform random numbers in [0, 1).
+pub(super) fn sample_barycentric((u0, u1): (f64, f64)) -> (f64, f64) {
+    let s = u0.sqrt();
+    (1. - s, u1 * s)
+}
+
+/// Compute the intersection of a ray (with a specified range) and a triangle,
+/// using the Möller–Trumbore algorithm.
+///
+/// Returns the parameter `t` and the barycentric coordinates `(b1, b2)` with respect to the
+/// second and third vertices, i.e. the hit point is `(1 - b1 - b2) p0 + b1 p1 + b2 p2`.
+pub(super) fn intersect(
+    ray: &Ray,
+    [p0, p1, p2]: &[na::Point3<f64>; 3],
+    (min_t, max_t): (f64, f64),
+) -> Option<(f64, f64, f64)> {
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+    let p = ray.direction.cross(&e2);
+    let det = e1.dot(&p);
+    // The ray is parallel to the plane of the triangle, or the triangle is degenerate.
+    if det == 0. {
+        return None;
+    }
+    let inv_det = 1. / det;
+
+    let s = ray.origin - p0;
+    let b1 = s.dot(&p) * inv_det;
+    if !(0. ..=1.).contains(&b1) {
+        return None;
+    }
+
+    let q = s.cross(&e1);
+    let b2 = ray.direction.dot(&q) * inv_det;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+
+    let t = e2.dot(&q) * inv_det;
+    if t <= min_t || max_t <= t {
+        return None;
+    }
+    Some((t, b1, b2))
+}
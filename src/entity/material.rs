@@ -3,17 +3,24 @@
 
 /// Implement [`Dielectric`] as a [`Material`].
 mod dielectric;
+/// Implement [`DiffuseLight`] as a [`Material`].
+mod diffuse_light;
 /// Implement [`Lambertian`] as a [`Material`].
 mod lambertian;
-/// Implement [`Lambertian`] as a [`Material`].
+/// Implement [`Metal`] as a [`Material`].
 mod metal;
 
 /// Re-export the implemented material types.
-pub use self::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
+pub use self::{
+    dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+};
 
 use super::geometry::GeometryHit;
+use super::texture::{SolidColor, Texture};
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use nalgebra as na;
+use std::sync::Arc;
 
 /// Defines the information of the scattered ray.
 pub struct ScatteredRay {
@@ -22,13 +29,83 @@ pub struct ScatteredRay {
     /// The decay on the color, for three channels (RGB) respectively. The decay is multiplicative,
     /// i.e. here (0., 0., 0.) means the scattered ray vanishes, and (1., 1., 1.) means the
     /// intensity is unchanged after scattering.
+    ///
+    /// For non-specular scattering, this is [`Material::evaluate`] divided by the density.
     pub decay: na::Vector3<f64>,
+    /// The probability density (with respect to solid angle) of the scattered direction.
+    /// This is only meaningful for diffuse scattering.
+    pub pdf: f64,
+    /// The kind of the scattering.
+    pub kind: ScatterKind,
+}
+
+/// Defines the kinds of scattering, which are limited separately along a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScatterKind {
+    /// Reflection into a direction described by [`Material::evaluate`] and [`Material::pdf`].
+    /// Light sources are only sampled for diffuse scattering.
+    Diffuse,
+    /// Reflection into a discrete set of directions, e.g. a mirror, or into any distribution
+    /// that [`Material::evaluate`] and [`Material::pdf`] cannot describe.
+    Specular,
+    /// Refraction through the surface.
+    Transmission,
 }
 
-/// A trait that computes the scattered ray given the incident ray and the hit information.
+/// A trait that describes how light is scattered (and emitted) on the surface.
+///
+/// Materials expose both the sampling of scattered directions and the evaluation of given
+/// directions, so that the renderer can combine scattering with sampling lights directly.
 pub trait Material: Send + Sync {
-    /// Compute the scattered ray.
-    fn scatter(&self, ray: &Ray, hit: &GeometryHit) -> ScatteredRay;
+    /// Sample the scattered ray, drawing the random numbers from the sampler.
+    ///
+    /// Returns `None` if the ray is absorbed.
+    fn sample(
+        &self,
+        ray: &Ray,
+        hit: &GeometryHit,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatteredRay>;
+
+    /// Evaluate the scattering from the incident ray towards the given direction, i.e. the BSDF
+    /// times the cosine factor. Note that the direction vector is not necessarily a unit vector.
+    ///
+    /// Specular scattering cannot be evaluated, so the default implementation returns black.
+    fn evaluate(
+        &self,
+        _ray: &Ray,
+        _hit: &GeometryHit,
+        _direction: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        na::Vector3::zeros()
+    }
+
+    /// The probability density (with respect to solid angle) that [`Material::sample`] returns
+    /// the given direction. Note that the direction vector is not necessarily a unit vector.
+    ///
+    /// Specular scattering has no density, so the default implementation returns 0.
+    fn pdf(&self, _ray: &Ray, _hit: &GeometryHit, _direction: &na::Vector3<f64>) -> f64 {
+        0.
+    }
+
+    /// Compute the radiance emitted from the hit point towards the origin of the ray.
+    ///
+    /// Most materials do not emit light, so the default implementation returns black.
+    fn emitted(&self, _ray: &Ray, _hit: &GeometryHit) -> na::Vector3<f64> {
+        na::Vector3::zeros()
+    }
+
+    /// The reflectance of the surface at the hit point, ignoring the directions of light.
+    ///
+    /// This is only used for inspecting scenes, so the default implementation returns black.
+    fn albedo(&self, _hit: &GeometryHit) -> na::Vector3<f64> {
+        na::Vector3::zeros()
+    }
+
+    /// Whether the material emits light, so that entities made of it are sampled as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
 }
 
 /// Compute the refraction of a ray given the direction and the normal.
@@ -53,3 +130,8 @@ fn refract(
 fn reflect(direction: na::Vector3<f64>, normal: na::UnitVector3<f64>) -> na::Vector3<f64> {
     direction - 2. * direction.dot(&normal) * normal.as_ref()
 }
+
+/// Wrap a constant color into a texture.
+fn solid(color: na::Vector3<f64>) -> Arc<dyn Texture> {
+    Arc::new(SolidColor::new(color))
+}
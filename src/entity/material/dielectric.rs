@@ -1,12 +1,16 @@
 //! Implement the [`Dielectric`] material in 3D space, which models refraction and reflection.
 
-use super::{reflect, refract, GeometryHit, Material, Ray, ScatteredRay};
+use super::{
+    reflect, refract, solid, GeometryHit, Material, Ray, Sampler, ScatterKind, ScatteredRay,
+    Texture,
+};
 use nalgebra as na;
+use std::sync::Arc;
 
 /// Refraction and reflection.
 pub struct Dielectric {
     /// The attenuation on three color channels.
-    albedo: na::Vector3<f64>,
+    albedo: Arc<dyn Texture>,
     /// The refractive index of the material, with respect to the exterior medium.
     ri: f64,
 }
@@ -14,34 +18,51 @@ pub struct Dielectric {
 impl Dielectric {
     /// Create a new [`Dielectric`] material with the given albedo and refractive index.
     pub fn new(albedo: na::Vector3<f64>, ri: f64) -> Self {
+        Self::from_texture(solid(albedo), ri)
+    }
+
+    /// Create a new [`Dielectric`] material whose albedo is given by a texture.
+    pub fn from_texture(albedo: Arc<dyn Texture>, ri: f64) -> Self {
         Self { albedo, ri }
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hit: &GeometryHit) -> ScatteredRay {
+    fn sample(
+        &self,
+        ray: &Ray,
+        hit: &GeometryHit,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatteredRay> {
         let ri = if hit.exterior { 1. / self.ri } else { self.ri };
 
         let unit_in = na::UnitVector3::new_normalize(ray.direction);
-        let direction = match refract(unit_in, hit.normal, ri) {
+        let reflected = (reflect(ray.direction, hit.normal), ScatterKind::Specular);
+        let (direction, kind) = match refract(unit_in, hit.normal, ri) {
             Some(refracted) => {
                 // Use Schlick's approximation for reflectance.
                 let cosine = -unit_in.dot(&hit.normal).min(1.);
                 let r = ((1. - ri) / (1. + ri)).powi(2);
                 let reflectance = r + (1. - r) * (1. - cosine).powi(5);
                 // Reflect with a certain probability.
-                if rand::random::<f64>() < reflectance {
-                    reflect(ray.direction, hit.normal)
+                if sampler.next_1d() < reflectance {
+                    reflected
                 } else {
-                    refracted
+                    (refracted, ScatterKind::Transmission)
                 }
             }
-            None => reflect(ray.direction, hit.normal),
+            None => reflected,
         };
 
-        ScatteredRay {
+        Some(ScatteredRay {
             ray: Ray::new(hit.point, direction),
-            decay: self.albedo,
-        }
+            decay: self.albedo.value(&hit.uv, &hit.point),
+            pdf: 0.,
+            kind,
+        })
+    }
+
+    fn albedo(&self, hit: &GeometryHit) -> na::Vector3<f64> {
+        self.albedo.value(&hit.uv, &hit.point)
     }
 }
@@ -0,0 +1,52 @@
+//! Implement the [`DiffuseLight`] material in 3D space, which models an area light source.
+
+use super::{GeometryHit, Material, Ray, Sampler, ScatteredRay};
+use nalgebra as na;
+
+/// Uniform emission in all directions, without scattering any light.
+pub struct DiffuseLight {
+    /// The emitted radiance on three color channels. Values may exceed 1.
+    emit: na::Vector3<f64>,
+    /// Whether the light is emitted from both sides of the surface.
+    two_sided: bool,
+}
+
+impl DiffuseLight {
+    /// Create a new [`DiffuseLight`] material with the given radiance, which only emits light
+    /// from the exterior side of the surface.
+    pub fn new(emit: na::Vector3<f64>) -> Self {
+        Self {
+            emit,
+            two_sided: false,
+        }
+    }
+
+    /// Emit light from both sides of the surface.
+    pub fn two_sided(mut self) -> Self {
+        self.two_sided = true;
+        self
+    }
+}
+
+impl Material for DiffuseLight {
+    fn sample(
+        &self,
+        _ray: &Ray,
+        _hit: &GeometryHit,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<ScatteredRay> {
+        None
+    }
+
+    fn emitted(&self, _ray: &Ray, hit: &GeometryHit) -> na::Vector3<f64> {
+        if hit.exterior || self.two_sided {
+            self.emit
+        } else {
+            na::Vector3::zeros()
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
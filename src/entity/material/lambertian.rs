@@ -1,32 +1,77 @@
 //! Implement the [`Lambertian`] material in 3D space, which models diffuse reflection.
 
-use super::{GeometryHit, Material, Ray, ScatteredRay};
-use crate::utils::{near_zero, random_unit_vector};
+use super::{solid, GeometryHit, Material, Ray, Sampler, ScatterKind, ScatteredRay, Texture};
+use crate::utils::{near_zero, sample_unit_vector};
 use nalgebra as na;
+use std::f64::consts::FRAC_1_PI;
+use std::sync::Arc;
 
 /// Diffuse reflection.
 pub struct Lambertian {
     /// The attenuation on three color channels.
-    albedo: na::Vector3<f64>,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     /// Create a new [`Lambertian`] material with the given albedo.
     pub fn new(albedo: na::Vector3<f64>) -> Self {
+        Self::from_texture(solid(albedo))
+    }
+
+    /// Create a new [`Lambertian`] material whose albedo is given by a texture.
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, hit: &GeometryHit) -> ScatteredRay {
-        let mut scatter_direction = *hit.normal + *random_unit_vector();
+    fn sample(
+        &self,
+        _ray: &Ray,
+        hit: &GeometryHit,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatteredRay> {
+        let mut scatter_direction = *hit.normal + *sample_unit_vector(sampler.next_2d());
         if near_zero(scatter_direction) {
             scatter_direction = *hit.normal;
         }
+        // Directions below the surface are absorbed, as in `evaluate`, which may happen when the
+        // shading normal differs from the geometric normal.
+        if scatter_direction.dot(&hit.geometric_normal) <= 0. {
+            return None;
+        }
 
-        ScatteredRay {
+        // The direction follows the cosine-weighted distribution around the normal, so the
+        // cosine factor cancels out with the density.
+        let cosine = scatter_direction.normalize().dot(&hit.normal).max(0.);
+        Some(ScatteredRay {
             ray: Ray::new(hit.point, scatter_direction),
-            decay: self.albedo,
+            decay: self.albedo.value(&hit.uv, &hit.point),
+            pdf: cosine * FRAC_1_PI,
+            kind: ScatterKind::Diffuse,
+        })
+    }
+
+    fn evaluate(
+        &self,
+        _ray: &Ray,
+        hit: &GeometryHit,
+        direction: &na::Vector3<f64>,
+    ) -> na::Vector3<f64> {
+        let direction = direction.normalize();
+        // Directions below the surface receive no light.
+        if direction.dot(&hit.geometric_normal) <= 0. {
+            return na::Vector3::zeros();
         }
+        let cosine = direction.dot(&hit.normal).max(0.);
+        self.albedo.value(&hit.uv, &hit.point) * cosine * FRAC_1_PI
+    }
+
+    fn pdf(&self, _ray: &Ray, hit: &GeometryHit, direction: &na::Vector3<f64>) -> f64 {
+        direction.normalize().dot(&hit.normal).max(0.) * FRAC_1_PI
+    }
+
+    fn albedo(&self, hit: &GeometryHit) -> na::Vector3<f64> {
+        self.albedo.value(&hit.uv, &hit.point)
     }
 }
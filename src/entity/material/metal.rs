@@ -1,13 +1,16 @@
 //! Implement the [`Metal`] material in 3D space, which models mirrored reflection.
 
-use super::{reflect, GeometryHit, Material, Ray, ScatteredRay};
-use crate::utils::random_unit_vector;
+use super::{
+    reflect, solid, GeometryHit, Material, Ray, Sampler, ScatterKind, ScatteredRay, Texture,
+};
+use crate::utils::sample_unit_vector;
 use nalgebra as na;
+use std::sync::Arc;
 
 /// Mirrored reflection.
 pub struct Metal {
     /// The attenuation on three color channels.
-    albedo: na::Vector3<f64>,
+    albedo: Arc<dyn Texture>,
     /// The randomness of the reflection, which is used to simulate roughness.
     fuzz: f64,
 }
@@ -15,18 +18,37 @@ pub struct Metal {
 impl Metal {
     /// Create a new [`Metal`] material with the given albedo.
     pub fn new(albedo: na::Vector3<f64>, fuzz: f64) -> Self {
+        Self::from_texture(solid(albedo), fuzz)
+    }
+
+    /// Create a new [`Metal`] material whose albedo is given by a texture.
+    pub fn from_texture(albedo: Arc<dyn Texture>, fuzz: f64) -> Self {
         Self { albedo, fuzz }
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hit: &GeometryHit) -> ScatteredRay {
-        ScatteredRay {
-            ray: Ray::new(
-                hit.point,
-                reflect(ray.direction, hit.normal) + self.fuzz * *random_unit_vector(),
-            ),
-            decay: self.albedo,
+    fn sample(
+        &self,
+        ray: &Ray,
+        hit: &GeometryHit,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatteredRay> {
+        let reflected = reflect(ray.direction, hit.normal).normalize();
+        let direction = reflected + self.fuzz * *sample_unit_vector(sampler.next_2d());
+        // The fuzz may push the reflected ray below the surface, where it is absorbed.
+        if direction.dot(&hit.normal) <= 0. {
+            return None;
         }
+        Some(ScatteredRay {
+            ray: Ray::new(hit.point, direction),
+            decay: self.albedo.value(&hit.uv, &hit.point),
+            pdf: 0.,
+            kind: ScatterKind::Specular,
+        })
+    }
+
+    fn albedo(&self, hit: &GeometryHit) -> na::Vector3<f64> {
+        self.albedo.value(&hit.uv, &hit.point)
     }
 }
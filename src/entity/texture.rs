@@ -0,0 +1,24 @@
+//! This module defines the [`Texture`] trait, which should be implemented for
+//! a spatially varying color on a surface.
+
+/// Implement [`Checker`] as a [`Texture`].
+mod checker;
+/// Implement [`ImageTexture`] as a [`Texture`].
+mod image_texture;
+/// Implement [`SolidColor`] as a [`Texture`].
+mod solid;
+
+/// Re-export the implemented textures.
+pub use self::{
+    checker::Checker,
+    image_texture::{ImageTexture, WrapMode},
+    solid::SolidColor,
+};
+
+use nalgebra as na;
+
+/// A trait that computes the color of a surface at a hit point.
+pub trait Texture: Send + Sync {
+    /// Sample the color at the given texture coordinates (UV) and hit point.
+    fn value(&self, uv: &na::Point2<f64>, point: &na::Point3<f64>) -> na::Vector3<f64>;
+}
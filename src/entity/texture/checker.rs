@@ -0,0 +1,51 @@
+//! Implement the [`Checker`] texture, which alternates two textures in 3D space.
+
+use super::{SolidColor, Texture};
+use nalgebra as na;
+use std::sync::Arc;
+
+/// A 3D checker pattern, which divides the space into cubes and alternates two textures
+/// between adjacent cubes. Since it depends on the hit point only, it needs no texture
+/// coordinates.
+pub struct Checker {
+    /// The reciprocal of the side length of each cube.
+    inv_size: f64,
+    /// The texture used in cubes where the sum of the cube coordinates is even.
+    even: Arc<dyn Texture>,
+    /// The texture used in cubes where the sum of the cube coordinates is odd.
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    /// Create a new [`Checker`] texture with the given cube size and textures.
+    pub fn new(size: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_size: 1. / size,
+            even,
+            odd,
+        }
+    }
+
+    /// Create a new [`Checker`] texture with the given cube size and two colors.
+    pub fn from_colors(size: f64, even: na::Vector3<f64>, odd: na::Vector3<f64>) -> Self {
+        Self::new(
+            size,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, uv: &na::Point2<f64>, point: &na::Point3<f64>) -> na::Vector3<f64> {
+        let sum: i64 = point
+            .iter()
+            .map(|&x| (x * self.inv_size).floor() as i64)
+            .sum();
+        if sum.rem_euclid(2) == 0 {
+            self.even.value(uv, point)
+        } else {
+            self.odd.value(uv, point)
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Implement the [`ImageTexture`], which maps an image onto a surface by texture coordinates.
+
+use super::Texture;
+use nalgebra as na;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Defines how texture coordinates outside [0, 1] are mapped onto the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Tile the image periodically.
+    Repeat,
+    /// Tile the image periodically, mirroring every other tile.
+    Mirror,
+    /// Extend the pixels on the border of the image.
+    Clamp,
+}
+
+impl WrapMode {
+    /// Map a (possibly out-of-range) pixel index into [0, size).
+    fn apply(self, index: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let index = match self {
+            Self::Repeat => index.rem_euclid(size),
+            Self::Mirror => {
+                let index = index.rem_euclid(2 * size);
+                if index < size {
+                    index
+                } else {
+                    2 * size - 1 - index
+                }
+            }
+            Self::Clamp => index.clamp(0, size - 1),
+        };
+        index as u32
+    }
+}
+
+/// A texture sampled from an image with bilinear filtering.
+///
+/// The texture coordinate (0, 0) is the bottom-left corner of the image, and (1, 1) is the
+/// top-right corner. Pixel values are used as they are stored, i.e. without gamma decoding,
+/// consistent with [`crate::utils::into_image`].
+///
+/// Cloning an [`ImageTexture`] shares the pixels, so the same image can be used with different
+/// wrap modes or tints without copying it.
+#[derive(Clone)]
+pub struct ImageTexture {
+    /// The pixels of the image.
+    image: Arc<image::Rgb32FImage>,
+    /// How to handle coordinates outside the image in each direction.
+    wrap: (WrapMode, WrapMode),
+    /// A color multiplied with the sampled values.
+    tint: na::Vector3<f64>,
+}
+
+impl ImageTexture {
+    /// Create a new [`ImageTexture`] from an image, which repeats in both directions.
+    ///
+    /// Panics if the image is empty.
+    pub fn new(image: image::DynamicImage) -> Self {
+        assert!(
+            image.width() > 0 && image.height() > 0,
+            "ImageTexture: the image should not be empty."
+        );
+        Self {
+            image: Arc::new(image.into_rgb32f()),
+            wrap: (WrapMode::Repeat, WrapMode::Repeat),
+            tint: na::vector![1., 1., 1.],
+        }
+    }
+
+    /// Load an image file as a texture.
+    pub fn open(path: impl AsRef<Path>) -> image::ImageResult<Self> {
+        Ok(Self::new(image::open(path)?))
+    }
+
+    /// Set the wrap mode in horizontal (U) and vertical (V) directions.
+    pub fn with_wrap(mut self, u: WrapMode, v: WrapMode) -> Self {
+        self.wrap = (u, v);
+        self
+    }
+
+    /// Multiply the sampled colors by a constant color.
+    pub fn with_tint(mut self, tint: na::Vector3<f64>) -> Self {
+        self.tint = tint;
+        self
+    }
+}
+
+impl ImageTexture {
+    /// Obtain the pixels of the image, without wrap modes or tint applied.
+    pub fn image(&self) -> &image::Rgb32FImage {
+        &self.image
+    }
+
+    /// Obtain the color of a pixel, where out-of-range indices are wrapped.
+    fn texel(&self, x: i64, y: i64) -> na::Vector3<f64> {
+        let x = self.wrap.0.apply(x, self.image.width());
+        let y = self.wrap.1.apply(y, self.image.height());
+        let [r, g, b] = self.image.get_pixel(x, y).0;
+        na::vector![r, g, b].cast()
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, uv: &na::Point2<f64>, _point: &na::Point3<f64>) -> na::Vector3<f64> {
+        // Pixel centers are located at half-integer coordinates.
+        let x = uv.x * self.image.width() as f64 - 0.5;
+        let y = (1. - uv.y) * self.image.height() as f64 - 0.5;
+        if !x.is_finite() || !y.is_finite() {
+            return na::Vector3::zeros();
+        }
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0).lerp(&self.texel(x0 + 1, y0), fx);
+        let bottom = self.texel(x0, y0 + 1).lerp(&self.texel(x0 + 1, y0 + 1), fx);
+        top.lerp(&bottom, fy).component_mul(&self.tint)
+    }
+}
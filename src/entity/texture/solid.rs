@@ -0,0 +1,23 @@
+//! Implement the [`SolidColor`] texture, which is constant everywhere.
+
+use super::Texture;
+use nalgebra as na;
+
+/// A texture with the same color everywhere.
+pub struct SolidColor {
+    /// The color on three channels.
+    color: na::Vector3<f64>,
+}
+
+impl SolidColor {
+    /// Create a new [`SolidColor`] texture with the given color.
+    pub fn new(color: na::Vector3<f64>) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _uv: &na::Point2<f64>, _point: &na::Point3<f64>) -> na::Vector3<f64> {
+        self.color
+    }
+}
@@ -0,0 +1,134 @@
+//! This module defines the [`Integrator`] trait, which should be implemented for an algorithm
+//! that computes the light transported to the camera.
+
+/// Implement [`AmbientOcclusion`] as an [`Integrator`].
+mod ambient_occlusion;
+/// Implement [`AovIntegrator`] as an [`Integrator`].
+mod aov;
+/// Implement [`BidirectionalPathTracer`] as an [`Integrator`].
+mod bidirectional;
+/// Implement [`DirectLighting`] as an [`Integrator`].
+mod direct;
+/// Implement [`MetropolisLightTransport`] as an [`Integrator`].
+mod metropolis;
+/// Implement [`PathTracer`] as an [`Integrator`].
+mod path;
+/// Implement [`PhotonMapper`] as an [`Integrator`].
+mod photon;
+
+/// Re-export the implemented integrators.
+pub use self::{
+    ambient_occlusion::AmbientOcclusion,
+    aov::{Aov, AovIntegrator},
+    bidirectional::BidirectionalPathTracer,
+    direct::DirectLighting,
+    metropolis::MetropolisLightTransport,
+    path::PathTracer,
+    photon::PhotonMapper,
+};
+
+use crate::camera::Film;
+use crate::entity::{GeometryHit, Material, ScatteredRay, World};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::utils::power_heuristic;
+use nalgebra as na;
+
+/// A trait that estimates the radiance arriving at the camera along a ray.
+///
+/// The camera calls the integrator once per sample, and averages the estimates of each pixel.
+pub trait Integrator: Send + Sync {
+    /// Estimate the radiance arriving at the origin of the ray, travelling against its
+    /// direction, in the given world.
+    ///
+    /// Light found on the way that arrives at other pixels of the image, e.g. when tracing
+    /// paths from lights, may be splatted onto the film instead. All random numbers are drawn
+    /// from the sampler.
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64>;
+
+    /// Prepare for rendering a pass onto the film, before estimating the radiance of any sample
+    /// in it, e.g. by tracing photons from lights and keeping them as the state of the film.
+    ///
+    /// The samples of a pass are rendered in parallel afterwards, sharing the film, so only
+    /// here may the state of the film be changed.
+    /// The default implementation does nothing.
+    fn begin_pass(&self, _world: &World, _film: &mut Film, _sampler: &mut dyn Sampler) {}
+}
+
+/// Compute the weight of the light found by a scattered ray on the entity of the given index
+/// (or the background if `None`), against the chance of sampling it as a light at the origin
+/// of the ray.
+///
+/// `scatter_pdf` is the density of the scattering that produced the ray, or `None` if lights
+/// were not sampled at its origin, in which case the light is fully counted.
+fn emission_weight(
+    ray: &Ray,
+    entity: Option<usize>,
+    scatter_pdf: Option<f64>,
+    world: &World,
+) -> f64 {
+    scatter_pdf.map_or(1., |pdf| {
+        power_heuristic(pdf, world.light_pdf(&ray.origin, &ray.direction, entity))
+    })
+}
+
+/// Estimate the light arriving at the hit point directly from the lights in the world, by
+/// sampling a light and casting a shadow ray towards it.
+fn sample_light(
+    ray: &Ray,
+    hit: &GeometryHit,
+    material: &dyn Material,
+    world: &World,
+    sampler: &mut dyn Sampler,
+) -> na::Vector3<f64> {
+    let choice = sampler.next_1d();
+    let Some(sample) = world.sample_light(&hit.point, choice, sampler.next_2d()) else {
+        return na::Vector3::zeros();
+    };
+    let decay = material.evaluate(ray, hit, &sample.direction);
+    if decay.iter().all(|&c| c <= 0.) || sample.radiance.iter().all(|&c| c <= 0.) {
+        return na::Vector3::zeros();
+    }
+    // Stop the shadow ray right before the light, which should not block itself.
+    let shadow = Ray::new(hit.point, *sample.direction);
+    if world.occluded(&shadow, (Ray::T_MIN, sample.distance * (1. - 1e-9))) {
+        return na::Vector3::zeros();
+    }
+    let weight = if sample.hittable {
+        power_heuristic(sample.pdf, material.pdf(ray, hit, &sample.direction))
+    } else {
+        1.
+    };
+    weight / sample.pdf * decay.component_mul(&sample.radiance)
+}
+
+/// Estimate the light arriving at the hit point directly from the lights in the world, and
+/// scattered towards the origin of the ray, by combining light sampling with the light found by
+/// the given diffuse scattering.
+fn direct_light(
+    ray: &Ray,
+    hit: &GeometryHit,
+    material: &dyn Material,
+    scattered: &ScatteredRay,
+    world: &World,
+    sampler: &mut dyn Sampler,
+) -> na::Vector3<f64> {
+    let direct = sample_light(ray, hit, material, world, sampler);
+    let emitted = match world.hit(&scattered.ray, (Ray::T_MIN, f64::INFINITY)) {
+        Some((i, next)) => (
+            Some(i),
+            world.entities()[i]
+                .material()
+                .emitted(&scattered.ray, &next),
+        ),
+        None => (None, world.background().radiance(&scattered.ray.direction)),
+    };
+    let weight = emission_weight(&scattered.ray, emitted.0, Some(scattered.pdf), world);
+    direct + weight * scattered.decay.component_mul(&emitted.1)
+}
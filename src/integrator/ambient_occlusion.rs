@@ -0,0 +1,82 @@
+//! Implement the [`AmbientOcclusion`] integrator, which shades the first hit of each camera ray
+//! by how much of its surroundings is open.
+
+use super::Integrator;
+use crate::camera::Film;
+use crate::entity::World;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::utils::{near_zero, sample_unit_vector};
+use nalgebra as na;
+
+/// Ambient occlusion, i.e. the cosine-weighted fraction of directions above the first hit that
+/// are not blocked within a maximum distance.
+///
+/// Materials and lights are ignored, so any scene gives a fast grey (clay) preview. Rays that
+/// hit nothing are white.
+pub struct AmbientOcclusion {
+    /// The distance beyond which entities no longer occlude the hit point.
+    max_distance: f64,
+    /// The number of occlusion rays cast from each hit point.
+    samples: u32,
+}
+
+impl AmbientOcclusion {
+    /// Create a new [`AmbientOcclusion`] integrator with the given maximum distance, casting one
+    /// occlusion ray from each hit point.
+    ///
+    /// Panics if `max_distance` is not positive.
+    pub fn new(max_distance: f64) -> Self {
+        assert!(
+            max_distance > 0.,
+            "AmbientOcclusion: `max_distance` should be positive."
+        );
+        Self {
+            max_distance,
+            samples: 1,
+        }
+    }
+
+    /// Set the number of occlusion rays cast from each hit point.
+    ///
+    /// Panics if `samples` is zero.
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        assert!(
+            samples > 0,
+            "AmbientOcclusion: `samples` should be positive."
+        );
+        self.samples = samples;
+        self
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        _film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        let Some((_, hit)) = world.hit(ray, (Ray::T_MIN, f64::INFINITY)) else {
+            return na::Vector3::repeat(1.);
+        };
+        let open = (0..self.samples)
+            .filter(|_| {
+                // Cosine-weighted directions make the fraction of open rays the estimate.
+                let mut direction = *hit.normal + *sample_unit_vector(sampler.next_2d());
+                if near_zero(direction) {
+                    direction = *hit.normal;
+                }
+                // Directions below the surface are blocked by the surface itself.
+                if direction.dot(&hit.geometric_normal) <= 0. {
+                    return false;
+                }
+                let direction = direction.normalize();
+                let occlusion = Ray::new(hit.point, direction);
+                !world.occluded(&occlusion, (Ray::T_MIN, self.max_distance))
+            })
+            .count();
+        na::Vector3::repeat(open as f64 / self.samples as f64)
+    }
+}
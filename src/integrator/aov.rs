@@ -0,0 +1,152 @@
+//! Implement the [`AovIntegrator`], which shows one kind of data about the first hit of each
+//! camera ray instead of light, for inspecting scenes.
+
+use super::Integrator;
+use crate::camera::Film;
+use crate::entity::World;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
+
+/// The kinds of data (arbitrary output variables) that [`AovIntegrator`] can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    /// The shading normal, mapped from [-1, 1] to [0, 1] on each axis.
+    Normal,
+    /// The depth in camera space, i.e. the distance from the camera along the direction it looks
+    /// to, so that flat surfaces facing the camera have the same depth everywhere. It is mapped
+    /// from [0, far] to [0, 1] in grey.
+    Depth,
+    /// The albedo of the material.
+    Albedo,
+    /// The texture coordinates, shown in the red and green channels.
+    Uv,
+    /// The index of the entity, shown as an arbitrary but distinct color per entity.
+    EntityIndex,
+    /// The number of scatterings before the path ends, mapped from [0, max] to [0, 1] in grey.
+    Bounces,
+}
+
+impl std::str::FromStr for Aov {
+    type Err = String;
+
+    /// Parse the name of an [`Aov`], e.g. `normal` or `entity-index`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "depth" => Ok(Self::Depth),
+            "albedo" => Ok(Self::Albedo),
+            "uv" => Ok(Self::Uv),
+            "entity-index" => Ok(Self::EntityIndex),
+            "bounces" => Ok(Self::Bounces),
+            _ => Err(format!("unknown AOV `{s}`")),
+        }
+    }
+}
+
+/// Show one kind of data about the first hit of each camera ray, as selected by [`Aov`].
+///
+/// Rays that hit nothing are black, except for [`Aov::Depth`] where they are white.
+pub struct AovIntegrator {
+    /// The kind of data to show.
+    aov: Aov,
+    /// The depth shown as white for [`Aov::Depth`].
+    far: f64,
+    /// The number of scatterings shown as white for [`Aov::Bounces`].
+    max_bounces: u32,
+}
+
+impl AovIntegrator {
+    /// Create a new [`AovIntegrator`] showing the given kind of data.
+    ///
+    /// By default, depth is shown up to 100, and bounces up to 16 scatterings.
+    pub fn new(aov: Aov) -> Self {
+        Self {
+            aov,
+            far: 100.,
+            max_bounces: 16,
+        }
+    }
+
+    /// Set the depth shown as white for [`Aov::Depth`].
+    ///
+    /// Panics if `far` is not positive.
+    pub fn with_far(mut self, far: f64) -> Self {
+        assert!(far > 0., "AovIntegrator: `far` should be positive.");
+        self.far = far;
+        self
+    }
+
+    /// Set the number of scatterings shown as white for [`Aov::Bounces`], after which paths are
+    /// terminated.
+    ///
+    /// Panics if `max` is zero.
+    pub fn with_max_bounces(mut self, max: u32) -> Self {
+        assert!(max > 0, "AovIntegrator: `max` should be positive.");
+        self.max_bounces = max;
+        self
+    }
+
+    /// Count the scatterings along the path starting from the ray, up to the maximum.
+    fn bounces(&self, ray: &Ray, world: &World, sampler: &mut dyn Sampler) -> u32 {
+        let mut light = ray.clone();
+        for depth in 0..self.max_bounces {
+            let Some((i, hit)) = world.hit(&light, (Ray::T_MIN, f64::INFINITY)) else {
+                return depth;
+            };
+            let material = world.entities()[i].material();
+            let Some(scattered) = material.sample(&light, &hit, sampler) else {
+                return depth;
+            };
+            light = scattered.ray;
+        }
+        self.max_bounces
+    }
+}
+
+/// Map an index to a bright color, such that adjacent indices look clearly different.
+fn index_color(index: usize) -> na::Vector3<f64> {
+    // Step the hue by the golden ratio, which spreads consecutive hues evenly.
+    let hue = (index as f64 * 0.618_033_988_749_895).fract() * 6.;
+    let x = 1. - (hue % 2. - 1.).abs();
+    match hue as u32 {
+        0 => na::vector![1., x, 0.],
+        1 => na::vector![x, 1., 0.],
+        2 => na::vector![0., 1., x],
+        3 => na::vector![0., x, 1.],
+        4 => na::vector![x, 0., 1.],
+        _ => na::vector![1., 0., x],
+    }
+}
+
+impl Integrator for AovIntegrator {
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        if self.aov == Aov::Bounces {
+            let bounces = self.bounces(ray, world, sampler) as f64 / self.max_bounces as f64;
+            return na::Vector3::repeat(bounces);
+        }
+        let Some((i, hit)) = world.hit(ray, (Ray::T_MIN, f64::INFINITY)) else {
+            return match self.aov {
+                Aov::Depth => na::Vector3::repeat(1.),
+                _ => na::Vector3::zeros(),
+            };
+        };
+        match self.aov {
+            Aov::Normal => (hit.normal.into_inner() + na::Vector3::repeat(1.)) / 2.,
+            Aov::Depth => {
+                let depth = (hit.point - ray.origin).dot(&film.camera().forward());
+                na::Vector3::repeat((depth / self.far).clamp(0., 1.))
+            }
+            Aov::Albedo => world.entities()[i].material().albedo(&hit),
+            Aov::Uv => na::vector![hit.uv.x, hit.uv.y, 0.],
+            Aov::EntityIndex => index_color(i),
+            Aov::Bounces => unreachable!(),
+        }
+    }
+}
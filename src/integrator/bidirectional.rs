@@ -0,0 +1,654 @@
+//! Implement the [`BidirectionalPathTracer`], which connects paths traced from the camera with
+//! paths traced from lights.
+
+use super::Integrator;
+use crate::camera::{Camera, Film};
+use crate::entity::{GeometryHit, ScatterKind, World};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
+
+/// Bidirectional path tracing.
+///
+/// For each sample, a path is traced from the camera and another from a light, and every
+/// vertex of one is connected to every vertex of the other. Each connection is a different
+/// strategy to build a path of the same length, and all of them are combined with multiple
+/// importance sampling. Connecting paths from lights directly to the camera splats light onto
+/// arbitrary pixels of the film, which renders caustics seen through a diffuse surface.
+///
+/// This costs much more per sample than [`PathTracer`], but converges far better when light
+/// only reaches the camera through specular surfaces or small openings.
+///
+/// [`PathTracer`]: super::PathTracer
+pub struct BidirectionalPathTracer {
+    /// The maximum number of scatterings of a path.
+    max_depth: usize,
+}
+
+impl BidirectionalPathTracer {
+    /// Create a new [`BidirectionalPathTracer`], building paths of at most 8 scatterings.
+    pub fn new() -> Self {
+        Self { max_depth: 8 }
+    }
+
+    /// Set the maximum number of scatterings of a path, counting neither the light nor the
+    /// camera.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl Default for BidirectionalPathTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Defines what a vertex of a path lies on.
+#[derive(Clone)]
+enum VertexKind {
+    /// A point on the lens of the camera.
+    Camera,
+    /// A point on a light, given the index of the light in the world and the direction that
+    /// light leaves it. For lights infinitely far away, only the direction is meaningful.
+    Light {
+        light: usize,
+        direction: na::UnitVector3<f64>,
+    },
+    /// A point on the surface of an entity, given the index of the entity, the hit record, and
+    /// the ray that reached it. The hit record is boxed, as it is far larger than other kinds.
+    Surface {
+        entity: usize,
+        hit: Box<GeometryHit>,
+        ray: Ray,
+    },
+    /// The background, reached by a path from the camera along the given direction.
+    Background { direction: na::UnitVector3<f64> },
+}
+
+/// Defines a vertex of a path traced from the camera or from a light.
+#[derive(Clone)]
+struct Vertex {
+    /// What the vertex lies on.
+    kind: VertexKind,
+    /// The position of the vertex.
+    point: na::Point3<f64>,
+    /// The geometric normal, if the vertex lies on a surface.
+    normal: Option<na::UnitVector3<f64>>,
+    /// The product of the decay factors (or radiance and importance for the endpoints) divided
+    /// by the densities along the path up to the vertex.
+    beta: na::Vector3<f64>,
+    /// Whether the path scatters specularly at the vertex, so that it cannot be connected.
+    delta: bool,
+    /// The probability density (with respect to area) of sampling the vertex from the previous
+    /// one along the path.
+    pdf_fwd: f64,
+    /// The probability density (with respect to area) of sampling the vertex from the next one,
+    /// i.e. if the path were traced in the opposite direction.
+    pdf_rev: f64,
+}
+
+impl Vertex {
+    /// Create a vertex with the given kind, position, normal and beta, whose densities are not
+    /// yet known.
+    fn new(
+        kind: VertexKind,
+        point: na::Point3<f64>,
+        normal: Option<na::UnitVector3<f64>>,
+        beta: na::Vector3<f64>,
+    ) -> Self {
+        Self {
+            kind,
+            point,
+            normal,
+            beta,
+            delta: false,
+            pdf_fwd: 0.,
+            pdf_rev: 0.,
+        }
+    }
+
+    /// Whether the vertex lies infinitely far away.
+    fn is_infinite(&self, world: &World) -> bool {
+        match self.kind {
+            VertexKind::Background { .. } => true,
+            VertexKind::Light { light, .. } => world.lights()[light].is_infinite(),
+            _ => false,
+        }
+    }
+
+    /// Whether the vertex can be connected to a vertex of the other path.
+    fn is_connectible(&self) -> bool {
+        match self.kind {
+            VertexKind::Camera | VertexKind::Light { .. } => true,
+            VertexKind::Surface { .. } => !self.delta,
+            VertexKind::Background { .. } => false,
+        }
+    }
+
+    /// The index in [`World::lights`] of the light that the vertex lies on, if any.
+    fn light(&self, world: &World) -> Option<usize> {
+        match self.kind {
+            VertexKind::Camera => None,
+            VertexKind::Light { light, .. } => Some(light),
+            VertexKind::Surface { entity, .. } => world.light_index(Some(entity)),
+            VertexKind::Background { .. } => world.light_index(None),
+        }
+    }
+
+    /// The direction that light leaves the vertex towards the given vertex.
+    ///
+    /// Light infinitely far away leaves along a fixed direction, whatever the given vertex is.
+    fn direction_to(&self, world: &World, next: &Vertex) -> na::Vector3<f64> {
+        match self.kind {
+            VertexKind::Light { direction, .. } if self.is_infinite(world) => *direction,
+            VertexKind::Background { direction } => -*direction,
+            _ => next.point - self.point,
+        }
+    }
+
+    /// Compute the light emitted from the vertex back along the path from the camera.
+    fn emitted(&self, world: &World) -> na::Vector3<f64> {
+        match &self.kind {
+            VertexKind::Surface { entity, hit, ray } => {
+                world.entities()[*entity].material().emitted(ray, hit)
+            }
+            VertexKind::Background { direction } => world.background().radiance(direction),
+            _ => na::Vector3::zeros(),
+        }
+    }
+
+    /// Evaluate the scattering at the vertex from the previous vertex along the path towards
+    /// the given vertex, i.e. the BSDF times the cosine factor.
+    fn scattering(&self, world: &World, next: &Vertex) -> na::Vector3<f64> {
+        match &self.kind {
+            VertexKind::Surface { entity, hit, ray } => world.entities()[*entity]
+                .material()
+                .evaluate(ray, hit, &(next.point - self.point)),
+            _ => na::Vector3::zeros(),
+        }
+    }
+
+    /// Convert the density (with respect to solid angle) of sampling the direction from the
+    /// vertex towards the given vertex into the density with respect to area there.
+    fn convert(&self, pdf: f64, next: &Vertex, world: &World) -> f64 {
+        if next.is_infinite(world) {
+            return pdf;
+        }
+        let offset = next.point - self.point;
+        let distance_squared = offset.norm_squared();
+        if distance_squared <= 0. {
+            return 0.;
+        }
+        let cosine = next
+            .normal
+            .map_or(1., |n| n.dot(&offset).abs() / distance_squared.sqrt());
+        pdf * cosine / distance_squared
+    }
+
+    /// The probability density (with respect to area) of sampling the given vertex from this
+    /// one, when the path reaches this vertex from `prev`.
+    fn pdf(&self, world: &World, camera: &Camera, prev: Option<&Vertex>, next: &Vertex) -> f64 {
+        let pdf = match &self.kind {
+            VertexKind::Camera => {
+                let ray = Ray::new(self.point, next.point - self.point);
+                camera.pdf_ray(&ray).1
+            }
+            VertexKind::Light { .. } => return self.pdf_light(world, next),
+            VertexKind::Surface { entity, hit, ray } => {
+                let ray = prev.map_or(ray.clone(), |prev| {
+                    Ray::new(prev.point, self.point - prev.point)
+                });
+                world.entities()[*entity]
+                    .material()
+                    .pdf(&ray, hit, &(next.point - self.point))
+            }
+            VertexKind::Background { .. } => return 0.,
+        };
+        self.convert(pdf, next, world)
+    }
+
+    /// The probability density (with respect to area) that the light at the vertex emits
+    /// towards the given vertex.
+    fn pdf_light(&self, world: &World, next: &Vertex) -> f64 {
+        let Some(index) = self.light(world) else {
+            return 0.;
+        };
+        let light = &world.lights()[index];
+        let Some(direction) = na::UnitVector3::try_new(self.direction_to(world, next), 0.) else {
+            return 0.;
+        };
+        let (pdf_position, pdf_direction) =
+            light.pdf_emission(world, &Ray::new(self.point, *direction));
+        if light.is_infinite() {
+            // Light infinitely far away enters the world through a disk facing the direction.
+            let cosine = next.normal.map_or(1., |n| n.dot(&direction).abs());
+            return pdf_position * cosine;
+        }
+        self.convert(pdf_direction, next, world)
+    }
+
+    /// The probability density that a path from lights starts at the vertex, towards the given
+    /// vertex. This is with respect to area, or solid angle for lights infinitely far away.
+    fn pdf_light_origin(&self, world: &World, next: &Vertex) -> f64 {
+        let Some(index) = self.light(world) else {
+            return 0.;
+        };
+        let light = &world.lights()[index];
+        let Some(direction) = na::UnitVector3::try_new(self.direction_to(world, next), 0.) else {
+            return 0.;
+        };
+        let (pdf_position, pdf_direction) =
+            light.pdf_emission(world, &Ray::new(self.point, *direction));
+        let pdf = if light.is_infinite() {
+            pdf_direction
+        } else {
+            pdf_position
+        };
+        pdf / world.lights().len() as f64
+    }
+
+    /// Copy the densities and the flag of specular scattering.
+    fn densities(&self) -> (f64, f64, bool) {
+        (self.pdf_fwd, self.pdf_rev, self.delta)
+    }
+}
+
+impl BidirectionalPathTracer {
+    /// Trace a path from the camera along the ray.
+    fn camera_path(
+        &self,
+        ray: &Ray,
+        world: &World,
+        camera: &Camera,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Vertex> {
+        let ray = Ray::new(ray.origin, ray.direction.normalize());
+        let (_, pdf) = camera.pdf_ray(&ray);
+        let mut path = vec![Vertex::new(
+            VertexKind::Camera,
+            ray.origin,
+            None,
+            na::Vector3::repeat(1.),
+        )];
+        let beta = na::Vector3::repeat(1.);
+        self.random_walk(world, ray, beta, pdf, &mut path, sampler);
+        path
+    }
+
+    /// Trace a path from a randomly chosen light.
+    fn light_path(&self, world: &World, sampler: &mut dyn Sampler) -> Vec<Vertex> {
+        let lights = world.lights();
+        if lights.is_empty() {
+            return Vec::new();
+        }
+        let index = ((sampler.next_1d() * lights.len() as f64) as usize).min(lights.len() - 1);
+        let light = &lights[index];
+        let choose = 1. / lights.len() as f64;
+        let (u, v) = (sampler.next_2d(), sampler.next_2d());
+        let Some(sample) = light.sample_emission(world, u, v) else {
+            return Vec::new();
+        };
+        if sample.pdf_position <= 0. || sample.pdf_direction <= 0. {
+            return Vec::new();
+        }
+        let direction = na::UnitVector3::new_normalize(sample.ray.direction);
+        let kind = VertexKind::Light {
+            light: index,
+            direction,
+        };
+        let pdf_fwd = sample.pdf_position * choose;
+        let beta = sample.radiance / pdf_fwd;
+        let mut origin = Vertex::new(kind, sample.ray.origin, sample.normal, beta);
+        origin.pdf_fwd = pdf_fwd;
+        let mut path = vec![origin];
+
+        let cosine = sample.normal.map_or(1., |n| n.dot(&direction).abs());
+        let beta = sample.radiance * cosine / (choose * sample.pdf_position * sample.pdf_direction);
+        let ray = Ray::new(sample.ray.origin, *direction);
+        self.random_walk(world, ray, beta, sample.pdf_direction, &mut path, sampler);
+
+        // Light infinitely far away is sampled by direction first, and then by position.
+        if light.is_infinite() {
+            if let Some(first) = path.get_mut(1) {
+                let cosine = first.normal.map_or(1., |n| n.dot(&direction).abs());
+                first.pdf_fwd = sample.pdf_position * cosine;
+            }
+            path[0].pdf_fwd = light.pdf_emission(world, &sample.ray).1 * choose;
+        }
+        path
+    }
+
+    /// Extend the path, which starts with the camera or a light, by scattering the ray, given
+    /// the beta and the density (with respect to solid angle) of the ray.
+    ///
+    /// Paths from the camera take one more scattering, as they have to reach a light by
+    /// themselves, and end with the background when they escape.
+    fn random_walk(
+        &self,
+        world: &World,
+        mut ray: Ray,
+        mut beta: na::Vector3<f64>,
+        mut pdf_fwd: f64,
+        path: &mut Vec<Vertex>,
+        sampler: &mut dyn Sampler,
+    ) {
+        let from_camera = matches!(path[0].kind, VertexKind::Camera);
+        let max = self.max_depth + usize::from(from_camera);
+        for depth in 0..max {
+            let Some((entity, hit)) = world.hit(&ray, (Ray::T_MIN, f64::INFINITY)) else {
+                if from_camera {
+                    let direction = na::UnitVector3::new_normalize(ray.direction);
+                    let kind = VertexKind::Background { direction };
+                    let mut vertex = Vertex::new(kind, ray.origin, None, beta);
+                    vertex.pdf_fwd = pdf_fwd;
+                    path.push(vertex);
+                }
+                return;
+            };
+            let material = world.entities()[entity].material();
+            let (point, normal) = (hit.point, hit.geometric_normal);
+            let kind = VertexKind::Surface {
+                entity,
+                hit: Box::new(hit.clone()),
+                ray: ray.clone(),
+            };
+            let mut vertex = Vertex::new(kind, point, Some(normal), beta);
+            vertex.pdf_fwd = path.last().unwrap().convert(pdf_fwd, &vertex, world);
+            path.push(vertex);
+
+            if depth + 1 == max {
+                return;
+            }
+            let Some(scattered) = material.sample(&ray, &hit, sampler) else {
+                return;
+            };
+            if scattered.decay.iter().all(|&c| c <= 0.) {
+                return;
+            }
+            beta.component_mul_assign(&scattered.decay);
+            let direction = scattered.ray.direction.normalize();
+
+            // Record the density of scattering in the opposite direction as well.
+            let n = path.len();
+            let pdf_rev = if scattered.kind == ScatterKind::Diffuse {
+                pdf_fwd = scattered.pdf;
+                let reverse = Ray::new(point + direction, -direction);
+                material.pdf(&reverse, &hit, &-ray.direction)
+            } else {
+                path[n - 1].delta = true;
+                pdf_fwd = 0.;
+                0.
+            };
+            path[n - 2].pdf_rev = path[n - 1].convert(pdf_rev, &path[n - 2], world);
+            ray = Ray::new(point, direction);
+        }
+    }
+}
+
+/// The paths traced for a sample, and the scene they are traced in, which all strategies
+/// connecting them share.
+struct Paths<'a> {
+    world: &'a World,
+    film: &'a Film<'a>,
+    light_path: Vec<Vertex>,
+    camera_path: Vec<Vertex>,
+}
+
+/// Defines the vertices that end the two parts of a path built by a strategy.
+///
+/// Vertices sampled on a light (`s = 1`) or the lens (`t = 1`) take the place of the last vertex
+/// of the respective path.
+enum Endpoints<'a> {
+    /// The path from the camera reaches a light `pt` by itself (`s = 0`) from `pt_minus`.
+    Emitter {
+        pt: &'a Vertex,
+        pt_minus: &'a Vertex,
+    },
+    /// The vertex `qs` of the light path is connected to the vertex `pt` of the camera path.
+    Connected { qs: &'a Vertex, pt: &'a Vertex },
+}
+
+impl Paths<'_> {
+    /// Compute the light of the path built by connecting the first `s` vertices of the light
+    /// path with the first `t` vertices of the camera path, weighted by multiple importance
+    /// sampling.
+    ///
+    /// If the path only consists of the light path and the camera (`t = 1`), the light is
+    /// splatted onto the film and black is returned.
+    fn connect(&self, s: usize, t: usize, sampler: &mut dyn Sampler) -> na::Vector3<f64> {
+        let (world, camera) = (self.world, self.film.camera());
+        let pt = &self.camera_path[t - 1];
+        if t > 1 && s != 0 && matches!(pt.kind, VertexKind::Background { .. }) {
+            return na::Vector3::zeros();
+        }
+
+        // The vertex sampled on the lens or a light, which the endpoints may borrow.
+        let sampled;
+        let mut pixel = None;
+        let (color, endpoints) = if s == 0 {
+            // The path from the camera reaches a light by itself.
+            let color = pt.beta.component_mul(&pt.emitted(world));
+            let pt_minus = &self.camera_path[t - 2];
+            (color, Endpoints::Emitter { pt, pt_minus })
+        } else if t == 1 {
+            // Connect the path from the light to a point sampled on the lens.
+            let qs = &self.light_path[s - 1];
+            if !qs.is_connectible() {
+                return na::Vector3::zeros();
+            }
+            let Some(sample) = camera.sample_importance(&qs.point, sampler.next_2d()) else {
+                return na::Vector3::zeros();
+            };
+            if sample.importance <= 0. || sample.pdf <= 0. {
+                return na::Vector3::zeros();
+            }
+            let point = qs.point + sample.distance * sample.direction.into_inner();
+            let beta = na::Vector3::repeat(sample.importance / sample.pdf);
+            sampled = Vertex::new(VertexKind::Camera, point, None, beta);
+            let color = qs
+                .beta
+                .component_mul(&qs.scattering(world, &sampled))
+                .component_mul(&beta);
+            let shadow = Ray::new(qs.point, *sample.direction);
+            if color == na::Vector3::zeros()
+                || world.occluded(&shadow, (Ray::T_MIN, sample.distance * (1. - 1e-9)))
+            {
+                return na::Vector3::zeros();
+            }
+            pixel = Some(sample.pixel);
+            (color, Endpoints::Connected { qs, pt: &sampled })
+        } else if s == 1 {
+            // Connect the path from the camera to a point sampled on a light.
+            if !pt.is_connectible() || world.lights().is_empty() {
+                return na::Vector3::zeros();
+            }
+            let count = world.lights().len();
+            let index = ((sampler.next_1d() * count as f64) as usize).min(count - 1);
+            let light = &world.lights()[index];
+            let Some(sample) = light.sample(world, &pt.point, sampler.next_2d()) else {
+                return na::Vector3::zeros();
+            };
+            if sample.pdf <= 0. {
+                return na::Vector3::zeros();
+            }
+            let distance = if sample.distance.is_finite() {
+                sample.distance
+            } else {
+                2. * world.bounding_sphere().1
+            };
+            let kind = VertexKind::Light {
+                light: index,
+                direction: -sample.direction,
+            };
+            let point = pt.point + distance * sample.direction.into_inner();
+            let beta = sample.radiance * count as f64 / sample.pdf;
+            let mut vertex = Vertex::new(kind, point, sample.normal, beta);
+            vertex.pdf_fwd = vertex.pdf_light_origin(world, pt);
+            sampled = vertex;
+            let color = pt
+                .beta
+                .component_mul(&pt.scattering(world, &sampled))
+                .component_mul(&beta);
+            let shadow = Ray::new(pt.point, *sample.direction);
+            if color == na::Vector3::zeros()
+                || world.occluded(&shadow, (Ray::T_MIN, sample.distance * (1. - 1e-9)))
+            {
+                return na::Vector3::zeros();
+            }
+            (color, Endpoints::Connected { qs: &sampled, pt })
+        } else {
+            // Connect the two paths directly.
+            let qs = &self.light_path[s - 1];
+            if !qs.is_connectible() || !pt.is_connectible() {
+                return na::Vector3::zeros();
+            }
+            let offset = pt.point - qs.point;
+            let distance = offset.norm();
+            if distance <= 0. {
+                return na::Vector3::zeros();
+            }
+            let color = qs
+                .beta
+                .component_mul(&qs.scattering(world, pt))
+                .component_mul(&pt.scattering(world, qs))
+                .component_mul(&pt.beta)
+                / (distance * distance);
+            let shadow = Ray::new(qs.point, offset / distance);
+            if color == na::Vector3::zeros()
+                || world.occluded(&shadow, (Ray::T_MIN, distance * (1. - 1e-9)))
+            {
+                return na::Vector3::zeros();
+            }
+            (color, Endpoints::Connected { qs, pt })
+        };
+        if color == na::Vector3::zeros() {
+            return na::Vector3::zeros();
+        }
+
+        let weight = if s == 0 && pt.light(world).is_none() {
+            // The light cannot be reached from the other side, e.g. a background that is not
+            // sampled as a light.
+            1.
+        } else {
+            self.mis_weight(&endpoints, s, t)
+        };
+        match pixel {
+            Some(pixel) => {
+                self.film.splat(pixel, &(weight * color));
+                na::Vector3::zeros()
+            }
+            None => weight * color,
+        }
+    }
+
+    /// Compute the weight of the strategy connecting `s` vertices of the light path with `t`
+    /// vertices of the camera path by the power heuristic, against all other strategies that
+    /// build the same path, which ends at the given endpoints.
+    fn mis_weight(&self, endpoints: &Endpoints, s: usize, t: usize) -> f64 {
+        if s + t == 2 {
+            return 1.;
+        }
+        let (world, camera) = (self.world, self.film.camera());
+        let (light_path, camera_path) = (&self.light_path, &self.camera_path);
+        // Copy the densities, which are updated for the vertices where the paths connect.
+        let mut light: Vec<_> = light_path[..s].iter().map(Vertex::densities).collect();
+        let mut view: Vec<_> = camera_path[..t].iter().map(Vertex::densities).collect();
+        let (qs, pt) = match *endpoints {
+            Endpoints::Emitter { pt, .. } => (None, pt),
+            Endpoints::Connected { qs, pt } => (Some(qs), pt),
+        };
+        let qs_minus = (s > 1).then(|| &light_path[s - 2]);
+        let pt_minus = (t > 1).then(|| &camera_path[t - 2]);
+        if let Some(qs) = qs.filter(|_| s == 1) {
+            light[0] = qs.densities();
+        }
+        if t == 1 {
+            view[0] = pt.densities();
+        }
+
+        // The connected vertices are not specular, as they have been connected.
+        view[t - 1].2 = false;
+        if s > 0 {
+            light[s - 1].2 = false;
+        }
+        view[t - 1].1 = match *endpoints {
+            Endpoints::Emitter { pt, pt_minus } => pt.pdf_light_origin(world, pt_minus),
+            Endpoints::Connected { qs, pt } => qs.pdf(world, camera, qs_minus, pt),
+        };
+        if let Some(pt_minus) = pt_minus {
+            view[t - 2].1 = match qs {
+                Some(qs) => pt.pdf(world, camera, Some(qs), pt_minus),
+                None => pt.pdf_light(world, pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light[s - 1].1 = pt.pdf(world, camera, pt_minus, qs);
+        }
+        if let (Some(qs), Some(qs_minus)) = (qs, qs_minus) {
+            light[s - 2].1 = qs.pdf(world, camera, Some(pt), qs_minus);
+        }
+
+        // Sum up the ratios of the densities of other strategies to this one, where specular
+        // vertices cannot be connected, and their densities are left out.
+        let remap = |pdf: f64| if pdf != 0. { pdf } else { 1. };
+        let mut sum = 0.;
+        let mut ratio = 1.;
+        for i in (1..t).rev() {
+            ratio *= remap(view[i].1) / remap(view[i].0);
+            if !view[i].2 && !view[i - 1].2 {
+                sum += ratio * ratio;
+            }
+        }
+        ratio = 1.;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].1) / remap(light[i].0);
+            let delta_light = if i > 0 {
+                light[i - 1].2
+            } else {
+                let origin = qs.filter(|_| s == 1).unwrap_or(&light_path[0]);
+                origin
+                    .light(world)
+                    .is_none_or(|index| world.lights()[index].is_delta())
+            };
+            if !light[i].2 && !delta_light {
+                sum += ratio * ratio;
+            }
+        }
+        1. / (1. + sum)
+    }
+}
+
+impl Integrator for BidirectionalPathTracer {
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        let camera_path = self.camera_path(ray, world, film.camera(), sampler);
+        let light_path = self.light_path(world, sampler);
+        let paths = Paths {
+            world,
+            film,
+            light_path,
+            camera_path,
+        };
+        let mut radiance = na::Vector3::zeros();
+        for t in 1..=paths.camera_path.len() {
+            for s in 0..=paths.light_path.len() {
+                // The number of scatterings of the connected path.
+                let Some(depth) = (s + t).checked_sub(2) else {
+                    continue;
+                };
+                if (s == 1 && t == 1) || depth > self.max_depth {
+                    continue;
+                }
+                radiance += paths.connect(s, t, sampler);
+            }
+        }
+        radiance
+    }
+}
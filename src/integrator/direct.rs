@@ -0,0 +1,79 @@
+//! Implement the [`DirectLighting`] integrator, which only computes light arriving directly
+//! from light sources.
+
+use super::{direct_light, Integrator};
+use crate::camera::Film;
+use crate::entity::{ScatterKind, World};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
+
+/// Direct lighting only, i.e. light scattered once by a diffuse surface towards the camera.
+///
+/// At the first diffuse scattering, lights are sampled directly and combined with one scattered
+/// ray by multiple importance sampling. Specular scattering and transmission are followed up to
+/// a maximum depth, so that mirrors and glass still show the directly lit world behind them.
+/// This is much faster than [`PathTracer`], but lacks indirect light.
+///
+/// [`PathTracer`]: super::PathTracer
+pub struct DirectLighting {
+    /// The maximum number of specular scatterings or transmissions followed.
+    max_specular_depth: u32,
+}
+
+impl DirectLighting {
+    /// Create a new [`DirectLighting`] integrator, following at most 8 specular scatterings or
+    /// transmissions.
+    pub fn new() -> Self {
+        Self {
+            max_specular_depth: 8,
+        }
+    }
+
+    /// Set the maximum number of specular scatterings or transmissions followed.
+    pub fn with_max_specular_depth(mut self, depth: u32) -> Self {
+        self.max_specular_depth = depth;
+        self
+    }
+}
+
+impl Default for DirectLighting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for DirectLighting {
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        _film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        let mut radiance = na::vector![0., 0., 0.];
+        let mut color = na::vector![1., 1., 1.];
+        let mut light = ray.clone();
+        for _ in 0..=self.max_specular_depth {
+            let Some((i, hit)) = world.hit(&light, (Ray::T_MIN, f64::INFINITY)) else {
+                let bg = world.background().radiance(&light.direction);
+                return radiance + color.component_mul(&bg);
+            };
+            let material = world.entities()[i].material();
+            radiance += color.component_mul(&material.emitted(&light, &hit));
+            let Some(ray) = material.sample(&light, &hit, sampler) else {
+                return radiance;
+            };
+            if ray.kind != ScatterKind::Diffuse {
+                color.component_mul_assign(&ray.decay);
+                light = ray.ray;
+                continue;
+            }
+
+            // Combine light sampling with the light found by the scattered ray.
+            let direct = direct_light(&light, &hit, material, &ray, world, sampler);
+            return radiance + color.component_mul(&direct);
+        }
+        radiance
+    }
+}
@@ -0,0 +1,214 @@
+//! Implement [`MetropolisLightTransport`], which explores the paths of the [`PathTracer`] with a
+//! Markov chain in primary sample space.
+
+/// Implement [`MetropolisSampler`] as a [`Sampler`].
+mod sampler;
+
+use self::sampler::MetropolisSampler;
+use super::{Integrator, PathTracer};
+use crate::camera::Film;
+use crate::distribution::Distribution1D;
+use crate::entity::World;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::utils::luminance;
+use nalgebra as na;
+
+/// Primary sample space Metropolis light transport (Kelemen et al., "A Simple and Robust
+/// Mutation Strategy for the Metropolis Light Transport Algorithm", 2002).
+///
+/// A path traced by the [`PathTracer`] is fully determined by the random numbers it draws,
+/// including the position on the image. Instead of drawing them independently for each sample,
+/// a Markov chain mutates them, accepting new paths in proportion to their brightness, so that
+/// bright paths that are hard to find (e.g. light through a narrow gap) are explored locally
+/// once found. Each pass runs its own chain, normalized by the brightness of independent paths
+/// traced before it starts.
+///
+/// The chain of each pass is run as the pass begins, with as many mutations as there are pixels,
+/// and the light of each mutation is splatted wherever it lands. The samples that the camera
+/// renders afterwards carry no light.
+pub struct MetropolisLightTransport {
+    /// The path tracer that computes the light of the paths.
+    path_tracer: PathTracer,
+    /// The number of independent paths traced to start each chain, or `None` for one per 4
+    /// pixels.
+    bootstrap: Option<usize>,
+    /// The probability of mutating the path by a large step.
+    large_step: f64,
+}
+
+impl MetropolisLightTransport {
+    /// Create a new [`MetropolisLightTransport`] integrator, with the default [`PathTracer`],
+    /// one bootstrap path per 4 pixels, and a probability of 0.3 for large steps.
+    pub fn new() -> Self {
+        Self {
+            path_tracer: PathTracer::new(),
+            bootstrap: None,
+            large_step: 0.3,
+        }
+    }
+
+    /// Set the path tracer that computes the light of the paths.
+    pub fn with_path_tracer(mut self, path_tracer: PathTracer) -> Self {
+        self.path_tracer = path_tracer;
+        self
+    }
+
+    /// Set the number of independent paths traced to start the chain of each pass.
+    ///
+    /// Panics if `count` is 0.
+    pub fn with_bootstrap(mut self, count: usize) -> Self {
+        assert!(
+            count > 0,
+            "MetropolisLightTransport: `count` should be positive."
+        );
+        self.bootstrap = Some(count);
+        self
+    }
+
+    /// Set the probability of mutating the path by a large step, i.e. drawing a new path
+    /// independently, instead of perturbing the current one.
+    ///
+    /// Panics if `probability` is not in [0, 1].
+    pub fn with_large_step(mut self, probability: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&probability),
+            "MetropolisLightTransport: `probability` should be in [0, 1]."
+        );
+        self.large_step = probability;
+        self
+    }
+}
+
+impl Default for MetropolisLightTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Defines the light of a path, and where it lands on the image.
+struct Contribution {
+    /// The position on the image, in pixels.
+    pixel: (f64, f64),
+    /// The light of the path.
+    color: na::Vector3<f64>,
+    /// The luminance of the light, which the chain is distributed in proportion to.
+    luminance: f64,
+}
+
+/// Defines the state of the Markov chain of a pass.
+struct Chain {
+    /// The sampler whose numbers determine the current path.
+    sampler: MetropolisSampler,
+    /// The light of the current path.
+    current: Contribution,
+    /// The average luminance of all paths, which scales the chain to the brightness of the
+    /// image.
+    normalization: f64,
+}
+
+impl MetropolisLightTransport {
+    /// Trace the path determined by the numbers of the sampler, starting from a position on the
+    /// image drawn from the sampler.
+    fn contribution(
+        &self,
+        world: &World,
+        film: &Film,
+        sampler: &mut MetropolisSampler,
+    ) -> Contribution {
+        let camera = film.camera();
+        let (u0, u1) = sampler.next_2d();
+        let pixel = (u0 * camera.width() as f64, u1 * camera.height() as f64);
+        let ray = camera.generate_ray(pixel, sampler.next_2d());
+        let color = self.path_tracer.radiance(&ray, world, film, sampler);
+        Contribution {
+            pixel,
+            color,
+            luminance: luminance(&color),
+        }
+    }
+
+    /// Mutate the path of the chain, and splat the light of both the current and the proposed
+    /// path, weighted by the chance of accepting the proposal.
+    fn mutate(&self, chain: &mut Chain, world: &World, film: &Film, sampler: &mut dyn Sampler) {
+        chain
+            .sampler
+            .start_iteration(sampler.next_1d() < self.large_step);
+        let proposed = self.contribution(world, film, &mut chain.sampler);
+        let accept = if chain.current.luminance > 0. {
+            (proposed.luminance / chain.current.luminance).min(1.)
+        } else {
+            1.
+        };
+
+        // Splat the expected light of the next state instead of the light of the chosen one.
+        let current = &chain.current;
+        if current.luminance > 0. {
+            let weight = (1. - accept) * chain.normalization / current.luminance;
+            film.splat(current.pixel, &(weight * current.color));
+        }
+        if proposed.luminance > 0. {
+            let weight = accept * chain.normalization / proposed.luminance;
+            film.splat(proposed.pixel, &(weight * proposed.color));
+        }
+
+        if sampler.next_1d() < accept {
+            chain.current = proposed;
+            chain.sampler.accept();
+        } else {
+            chain.sampler.reject();
+        }
+    }
+}
+
+impl Integrator for MetropolisLightTransport {
+    fn begin_pass(&self, world: &World, film: &mut Film, sampler: &mut dyn Sampler) {
+        let camera = film.camera();
+        let pixels = (camera.width() * camera.height()) as usize;
+        let count = self.bootstrap.unwrap_or(pixels.div_ceil(4));
+
+        // Trace independent paths, each replayable from its own seed, and estimate the average
+        // luminance of all paths from them.
+        let seed = (sampler.next_1d() * (1u64 << 53) as f64) as u64;
+        let luminances = (0..count as u64)
+            .map(|i| {
+                let mut bootstrap = MetropolisSampler::new(seed.wrapping_add(i));
+                bootstrap.start_iteration(true);
+                self.contribution(world, film, &mut bootstrap).luminance
+            })
+            .collect();
+        let distribution = Distribution1D::new(luminances);
+        let normalization = distribution.integral();
+        if normalization <= 0. {
+            return;
+        }
+
+        // Start the chain from a path chosen in proportion to its luminance, so that the chain
+        // is distributed as desired from the beginning.
+        let (index, _) = distribution.sample_discrete(sampler.next_1d());
+        let mut chain_sampler = MetropolisSampler::new(seed.wrapping_add(index as u64));
+        chain_sampler.start_iteration(true);
+        let current = self.contribution(world, film, &mut chain_sampler);
+        chain_sampler.accept();
+        let mut chain = Chain {
+            sampler: chain_sampler,
+            current,
+            normalization,
+        };
+        // Run the chain with one mutation per pixel, as many as the samples of other integrators.
+        for _ in 0..pixels {
+            self.mutate(&mut chain, world, film, sampler);
+        }
+    }
+
+    fn radiance(
+        &self,
+        _ray: &Ray,
+        _world: &World,
+        _film: &Film,
+        _sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        // All light has been splatted by the chain.
+        na::Vector3::zeros()
+    }
+}
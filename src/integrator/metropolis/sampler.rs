@@ -0,0 +1,138 @@
+//! Implement the [`MetropolisSampler`], which mutates the random numbers of a path in primary
+//! sample space.
+
+use crate::sampler::Sampler;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Defines a random number in primary sample space, with the last iteration that changed it.
+#[derive(Clone, Copy, Default)]
+struct PrimarySample {
+    /// The current value in [0, 1).
+    value: f64,
+    /// The iteration when the value was last changed.
+    modified: u64,
+    /// The value before the current iteration, restored if the mutation is rejected.
+    value_backup: f64,
+    /// The iteration when the backup was last changed.
+    modified_backup: u64,
+}
+
+/// A sampler whose numbers are mutated from one iteration to the next, so that a Markov chain
+/// can explore the paths they generate (Kelemen et al., "A Simple and Robust Mutation Strategy
+/// for the Metropolis Light Transport Algorithm", 2002).
+///
+/// A large step draws all numbers afresh, while a small step perturbs each of them slightly.
+/// Numbers are only mutated when drawn, catching up on the iterations they missed, so paths of
+/// any length are supported.
+pub struct MetropolisSampler {
+    /// The random generator that drives the mutations.
+    rng: StdRng,
+    /// The numbers drawn so far, in the order they are drawn in an iteration.
+    samples: Vec<PrimarySample>,
+    /// The index of the next number to draw in the current iteration.
+    index: usize,
+    /// The current iteration.
+    iteration: u64,
+    /// Whether the current iteration is a large step.
+    large_step: bool,
+    /// The last iteration that was an accepted large step.
+    last_large_step: u64,
+}
+
+impl MetropolisSampler {
+    /// The smallest perturbation of a small step.
+    const MIN_STEP: f64 = 1. / 1024.;
+    /// The largest perturbation of a small step.
+    const MAX_STEP: f64 = 1. / 64.;
+}
+
+impl MetropolisSampler {
+    /// Create a new [`MetropolisSampler`] whose mutations are driven by the given seed.
+    ///
+    /// Samplers with the same seed draw the same numbers, given the same steps.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            samples: Vec::new(),
+            index: 0,
+            iteration: 0,
+            large_step: true,
+            last_large_step: 0,
+        }
+    }
+
+    /// Start a new iteration, mutating the numbers by a large step or a small step.
+    pub fn start_iteration(&mut self, large_step: bool) {
+        self.iteration += 1;
+        self.large_step = large_step;
+        self.index = 0;
+    }
+
+    /// Keep the numbers drawn in the current iteration.
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    /// Restore the numbers from before the current iteration.
+    pub fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.modified == self.iteration {
+                sample.value = sample.value_backup;
+                sample.modified = sample.modified_backup;
+            }
+        }
+        self.iteration -= 1;
+    }
+
+    /// Perturb the value by a small step, whose size is exponentially distributed between the
+    /// smallest and the largest one, wrapping around [0, 1).
+    fn perturb(&mut self, value: f64) -> f64 {
+        let u = self.rng.gen::<f64>();
+        let (sign, u) = if u < 0.5 {
+            (1., 2. * u)
+        } else {
+            (-1., 2. * u - 1.)
+        };
+        let step = Self::MAX_STEP * (-(Self::MAX_STEP / Self::MIN_STEP).ln() * u).exp();
+        let value = (value + sign * step).rem_euclid(1.);
+        // Tiny negative values wrap around to exactly 1 after rounding.
+        if value < 1. {
+            value
+        } else {
+            0.
+        }
+    }
+}
+
+impl Sampler for MetropolisSampler {
+    fn next_1d(&mut self) -> f64 {
+        if self.index == self.samples.len() {
+            self.samples.push(PrimarySample::default());
+        }
+        let mut sample = self.samples[self.index];
+        self.index += 1;
+
+        // Numbers unused since the last accepted large step start from a fresh value then.
+        if sample.modified < self.last_large_step {
+            sample.value = self.rng.gen();
+            sample.modified = self.last_large_step;
+        }
+        sample.value_backup = sample.value;
+        sample.modified_backup = sample.modified;
+        if self.large_step {
+            sample.value = self.rng.gen();
+        } else {
+            // Apply the small steps of the iterations that did not draw this number.
+            for _ in sample.modified..self.iteration {
+                sample.value = self.perturb(sample.value);
+            }
+        }
+        sample.modified = self.iteration;
+
+        self.samples[self.index - 1] = sample;
+        sample.value
+    }
+}
@@ -0,0 +1,122 @@
+//! Implement the [`PathTracer`], which follows paths from the camera through any number of
+//! scatterings.
+
+use super::{emission_weight, sample_light, Integrator};
+use crate::camera::Film;
+use crate::entity::{ScatterKind, World};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
+
+/// Unidirectional path tracing with next-event estimation.
+///
+/// At each diffuse scattering, lights are sampled directly, and the light found by the
+/// scattered ray is weighted against that with multiple importance sampling. Paths are
+/// terminated by Russian roulette once they are deep enough.
+pub struct PathTracer {
+    /// The minimum and maximum depth of paths for each [`ScatterKind`].
+    depth: [(u32, u32); 3],
+}
+
+impl PathTracer {
+    /// The default minimum and maximum depth of paths for each [`ScatterKind`].
+    pub(crate) const DEFAULT_DEPTH: [(u32, u32); 3] = [(4, 64), (8, 128), (8, 128)];
+}
+
+impl PathTracer {
+    /// Create a new [`PathTracer`] with the default depth limits.
+    ///
+    /// The default limits are (4, 64) for diffuse scattering, and (8, 128) for specular
+    /// scattering and transmission, so that light passing through glass is rarely cut off.
+    pub fn new() -> Self {
+        Self {
+            depth: Self::DEFAULT_DEPTH,
+        }
+    }
+
+    /// Set the depth limits of paths for a kind of scattering.
+    ///
+    /// Once a path has scattered more than `min` times of this kind, it is randomly terminated
+    /// by Russian roulette based on its throughput, which keeps the result unbiased. A path is
+    /// always terminated after scattering `max` times of this kind.
+    ///
+    /// Panics if `min` exceeds `max`.
+    pub fn with_depth(mut self, kind: ScatterKind, min: u32, max: u32) -> Self {
+        assert!(min <= max, "PathTracer: `min` should not exceed `max`.");
+        self.depth[kind as usize] = (min, max);
+        self
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        _film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        // Record the radiance collected so far.
+        let mut radiance = na::vector![0., 0., 0.];
+        // Record the current decay factor.
+        let mut color = na::vector![1., 1., 1.];
+        // Record the current ray.
+        let mut light = ray.clone();
+        // Record the density of the scattering that produced the current ray, if lights were
+        // also sampled at its origin.
+        let mut scatter_pdf = None;
+        // Record the number of scatterings of each kind.
+        let mut depth = [0; 3];
+        loop {
+            let Some((i, hit)) = world.hit(&light, (Ray::T_MIN, f64::INFINITY)) else {
+                // Background.
+                let weight = emission_weight(&light, None, scatter_pdf, world);
+                let bg = world.background().radiance(&light.direction);
+                return radiance + weight * color.component_mul(&bg);
+            };
+            // Foreground objects.
+            let material = world.entities()[i].material();
+            let emitted = material.emitted(&light, &hit);
+            if emitted != na::Vector3::zeros() {
+                let weight = emission_weight(&light, Some(i), scatter_pdf, world);
+                radiance += weight * color.component_mul(&emitted);
+            }
+            let Some(ray) = material.sample(&light, &hit, sampler) else {
+                return radiance;
+            };
+            let diffuse = ray.kind == ScatterKind::Diffuse;
+            if diffuse {
+                let direct = sample_light(&light, &hit, material, world, sampler);
+                radiance += color.component_mul(&direct);
+            }
+            if ray.decay.iter().all(|&c| c < 1e-8) {
+                return radiance;
+            }
+            color.component_mul_assign(&ray.decay);
+
+            // Stop collecting light if the ray scatters too many times, or randomly once the
+            // path is deep enough, compensating surviving paths for the terminated ones.
+            let kind = ray.kind as usize;
+            let (min_depth, max_depth) = self.depth[kind];
+            depth[kind] += 1;
+            if depth[kind] > max_depth {
+                return radiance;
+            }
+            if depth[kind] > min_depth {
+                let survival = color.max().min(1.);
+                if sampler.next_1d() >= survival {
+                    return radiance;
+                }
+                color /= survival;
+            }
+            light = ray.ray;
+            scatter_pdf = diffuse.then_some(ray.pdf);
+        }
+    }
+}
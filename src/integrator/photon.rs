@@ -0,0 +1,245 @@
+//! Implement the [`PhotonMapper`], which traces photons from lights and estimates their density
+//! where paths from the camera meet diffuse surfaces.
+
+/// Implement [`PhotonMap`] as a kd-tree of photons.
+mod map;
+
+use self::map::{Photon, PhotonMap};
+use super::{direct_light, Integrator};
+use crate::camera::Film;
+use crate::entity::{ScatterKind, World};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use nalgebra as na;
+
+/// Progressive photon mapping.
+///
+/// Before each pass, photons are traced from the lights and stored where they meet diffuse
+/// surfaces. Paths from the camera follow specular scattering and transmission up to the first
+/// diffuse surface, where direct light is computed as in [`DirectLighting`], and the rest is
+/// estimated from the density of photons nearby. This renders caustics, e.g. light focused by
+/// glass onto a diffuse floor, much more smoothly than [`PathTracer`].
+///
+/// The density is estimated within a radius that shrinks from pass to pass, so the bias of the
+/// average vanishes as more passes are rendered (Knaus and Zwicker, "Progressive Photon
+/// Mapping: A Probabilistic Approach", 2011). Only lights emit photons, so light from a
+/// background that is not sampled as a light, e.g. a [`ConstantBackground`], is only found
+/// directly. Photons from the background enter through a disk as wide as the world, so very
+/// large entities, e.g. a huge sphere as the ground, spread them thin and call for more photons.
+///
+/// [`ConstantBackground`]: crate::background::ConstantBackground
+/// [`DirectLighting`]: super::DirectLighting
+/// [`PathTracer`]: super::PathTracer
+pub struct PhotonMapper {
+    /// The number of photons traced in each pass.
+    photons: usize,
+    /// The radius for gathering photons in the first pass, or `None` for 1% of the radius of
+    /// the world.
+    radius: Option<f64>,
+    /// The ratio of photons kept from one pass to the next, which controls how fast the radius
+    /// shrinks.
+    alpha: f64,
+    /// The maximum number of scatterings of photons and paths from the camera.
+    max_depth: u32,
+}
+
+impl PhotonMapper {
+    /// Create a new [`PhotonMapper`], tracing 100,000 photons in each pass, with an initial
+    /// radius of 1% of the world, and at most 16 scatterings.
+    pub fn new() -> Self {
+        Self {
+            photons: 100_000,
+            radius: None,
+            alpha: 2. / 3.,
+            max_depth: 16,
+        }
+    }
+
+    /// Set the number of photons traced from the lights in each pass.
+    ///
+    /// The photons of a pass are traced on one thread, while the passes prepared at once (8 of
+    /// them) are traced in parallel and keep their photons in memory together, which takes about
+    /// 80 bytes per stored photon.
+    /// Panics if `count` is 0.
+    pub fn with_photons(mut self, count: usize) -> Self {
+        assert!(count > 0, "PhotonMapper: `count` should be positive.");
+        self.photons = count;
+        self
+    }
+
+    /// Set the radius for gathering photons in the first pass.
+    ///
+    /// A larger radius gives smoother but blurrier results in the first passes.
+    /// Panics if `radius` is not positive.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        assert!(radius > 0., "PhotonMapper: `radius` should be positive.");
+        self.radius = Some(radius);
+        self
+    }
+
+    /// Set the ratio of photons kept from one pass to the next, in (0, 1). The radius shrinks
+    /// faster with smaller ratios, which reduces the bias but increases the noise.
+    ///
+    /// Panics if `alpha` is not in (0, 1).
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        assert!(
+            0. < alpha && alpha < 1.,
+            "PhotonMapper: `alpha` should be in (0, 1)."
+        );
+        self.alpha = alpha;
+        self
+    }
+
+    /// Set the maximum number of scatterings of photons and paths from the camera.
+    pub fn with_max_depth(mut self, depth: u32) -> Self {
+        self.max_depth = depth;
+        self
+    }
+}
+
+impl Default for PhotonMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Defines the photons traced for one pass.
+struct PassPhotons {
+    /// The photons stored on diffuse surfaces.
+    map: PhotonMap,
+    /// The radius for gathering photons in this pass.
+    radius: f64,
+}
+
+impl PhotonMapper {
+    /// Compute the radius for gathering photons in the pass of the given index.
+    ///
+    /// The area of the disk shrinks by `(i + alpha) / (i + 1)` after the `i`-th pass.
+    fn radius(&self, world: &World, pass: usize) -> f64 {
+        let initial = self
+            .radius
+            .unwrap_or_else(|| 0.01 * world.bounding_sphere().1);
+        let ratio: f64 = (1..=pass)
+            .map(|i| (i as f64 + self.alpha) / (i as f64 + 1.))
+            .product();
+        initial * ratio.sqrt()
+    }
+
+    /// Trace the given number of photons from randomly chosen lights, and store those arriving
+    /// at diffuse surfaces after at least one scattering. Light arriving directly is computed
+    /// by sampling the lights instead.
+    fn trace_photons(&self, world: &World, count: usize, sampler: &mut dyn Sampler) -> Vec<Photon> {
+        let lights = world.lights();
+        let mut photons = Vec::new();
+        if lights.is_empty() {
+            return photons;
+        }
+        let choose = 1. / lights.len() as f64;
+        for _ in 0..count {
+            let index = ((sampler.next_1d() * lights.len() as f64) as usize).min(lights.len() - 1);
+            let (u, v) = (sampler.next_2d(), sampler.next_2d());
+            let Some(sample) = lights[index].sample_emission(world, u, v) else {
+                continue;
+            };
+            let pdf = choose * sample.pdf_position * sample.pdf_direction;
+            if pdf <= 0. {
+                continue;
+            }
+            let cosine = sample
+                .normal
+                .map_or(1., |n| n.dot(&sample.ray.direction.normalize()).abs());
+            let mut power = sample.radiance * cosine / (pdf * count as f64);
+            if power.max() <= 0. {
+                continue;
+            }
+            let mut ray = sample.ray;
+
+            for depth in 0..self.max_depth {
+                let Some((i, hit)) = world.hit(&ray, (Ray::T_MIN, f64::INFINITY)) else {
+                    break;
+                };
+                let material = world.entities()[i].material();
+                let Some(scattered) = material.sample(&ray, &hit, sampler) else {
+                    break;
+                };
+                if scattered.kind == ScatterKind::Diffuse && depth > 0 {
+                    photons.push(Photon {
+                        point: hit.point,
+                        direction: na::UnitVector3::new_normalize(ray.direction),
+                        power,
+                    });
+                }
+
+                // Terminate photons randomly as they lose power, so that the stored photons
+                // carry similar power, compensating surviving photons for the terminated ones.
+                let scattered_power = power.component_mul(&scattered.decay);
+                let survival = (scattered_power.max() / power.max()).min(1.);
+                if sampler.next_1d() >= survival {
+                    break;
+                }
+                power = scattered_power / survival;
+                ray = scattered.ray;
+            }
+        }
+        photons
+    }
+}
+
+impl Integrator for PhotonMapper {
+    fn begin_pass(&self, world: &World, film: &mut Film, sampler: &mut dyn Sampler) {
+        let photons = PassPhotons {
+            map: PhotonMap::new(self.trace_photons(world, self.photons, sampler)),
+            radius: self.radius(world, film.pass()),
+        };
+        film.set_state(photons);
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        world: &World,
+        film: &Film,
+        sampler: &mut dyn Sampler,
+    ) -> na::Vector3<f64> {
+        let photons = film
+            .state::<PassPhotons>()
+            .expect("PhotonMapper: photons should be traced before rendering a pass.");
+        let mut radiance = na::vector![0., 0., 0.];
+        let mut color = na::vector![1., 1., 1.];
+        let mut light = ray.clone();
+        for _ in 0..=self.max_depth {
+            let Some((i, hit)) = world.hit(&light, (Ray::T_MIN, f64::INFINITY)) else {
+                let bg = world.background().radiance(&light.direction);
+                return radiance + color.component_mul(&bg);
+            };
+            let material = world.entities()[i].material();
+            radiance += color.component_mul(&material.emitted(&light, &hit));
+            let Some(ray) = material.sample(&light, &hit, sampler) else {
+                return radiance;
+            };
+            if ray.kind != ScatterKind::Diffuse {
+                color.component_mul_assign(&ray.decay);
+                light = ray.ray;
+                continue;
+            }
+
+            // Estimate the radiance from the photons within the radius, i.e. their power
+            // scattered towards the ray, over the area of the disk.
+            let mut gathered = na::Vector3::zeros();
+            photons
+                .map
+                .for_each_near(&hit.point, photons.radius, |photon| {
+                    let incoming = -photon.direction.into_inner();
+                    let cosine = incoming.dot(&hit.normal).abs();
+                    if cosine > 0. {
+                        let decay = material.evaluate(&light, &hit, &incoming) / cosine;
+                        gathered += decay.component_mul(&photon.power);
+                    }
+                });
+            let area = std::f64::consts::PI * photons.radius * photons.radius;
+            let direct = direct_light(&light, &hit, material, &ray, world, sampler);
+            return radiance + color.component_mul(&(direct + gathered / area));
+        }
+        radiance
+    }
+}
@@ -0,0 +1,102 @@
+//! Implement a [`PhotonMap`], which stores photons in a kd-tree to find those near a point.
+
+use nalgebra as na;
+
+/// Defines a photon, i.e. a packet of light that arrives at a surface.
+pub struct Photon {
+    /// The point where the photon arrives.
+    pub point: na::Point3<f64>,
+    /// The direction that the photon travels along.
+    pub direction: na::UnitVector3<f64>,
+    /// The power (flux) that the photon carries.
+    pub power: na::Vector3<f64>,
+}
+
+/// A balanced kd-tree over photons.
+///
+/// The tree is stored implicitly: each range of photons is split at its median along the axis
+/// of its largest extent, so the median photon is the node and the two halves are its subtrees.
+pub struct PhotonMap {
+    /// The photons, ordered as the nodes of the tree.
+    photons: Vec<Photon>,
+    /// The axis that each node splits its range along.
+    axes: Vec<u8>,
+}
+
+impl PhotonMap {
+    /// Build a kd-tree over the given photons.
+    pub fn new(mut photons: Vec<Photon>) -> Self {
+        let mut axes = vec![0; photons.len()];
+        Self::build(&mut photons, &mut axes);
+        Self { photons, axes }
+    }
+
+    /// Split the range of photons at its median recursively.
+    fn build(photons: &mut [Photon], axes: &mut [u8]) {
+        if photons.len() <= 1 {
+            return;
+        }
+        let (min, max) = photons.iter().fold(
+            (
+                na::Vector3::repeat(f64::INFINITY),
+                na::Vector3::repeat(f64::NEG_INFINITY),
+            ),
+            |(min, max), photon| (min.inf(&photon.point.coords), max.sup(&photon.point.coords)),
+        );
+        let axis = (max - min).imax();
+        let mid = photons.len() / 2;
+        photons.select_nth_unstable_by(mid, |a, b| a.point[axis].total_cmp(&b.point[axis]));
+        axes[mid] = axis as u8;
+
+        let (left, right) = photons.split_at_mut(mid);
+        let (left_axes, right_axes) = axes.split_at_mut(mid);
+        Self::build(left, left_axes);
+        Self::build(&mut right[1..], &mut right_axes[1..]);
+    }
+
+    /// Visit every photon within the given distance to the point.
+    pub fn for_each_near(
+        &self,
+        point: &na::Point3<f64>,
+        radius: f64,
+        mut visit: impl FnMut(&Photon),
+    ) {
+        self.search(0, self.photons.len(), point, radius * radius, &mut visit);
+    }
+
+    /// Visit the photons within the range `start..end` of the tree, which are no farther than
+    /// `sqrt(radius_squared)` to the point.
+    fn search(
+        &self,
+        start: usize,
+        end: usize,
+        point: &na::Point3<f64>,
+        radius_squared: f64,
+        visit: &mut impl FnMut(&Photon),
+    ) {
+        if start >= end {
+            return;
+        }
+        let mid = start + (end - start) / 2;
+        let photon = &self.photons[mid];
+        if (photon.point - point).norm_squared() <= radius_squared {
+            visit(photon);
+        }
+        if end - start == 1 {
+            return;
+        }
+
+        // Search the half containing the point first, and the other only if it is near enough.
+        let axis = self.axes[mid] as usize;
+        let offset = point[axis] - photon.point[axis];
+        let (near, far) = if offset < 0. {
+            ((start, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (start, mid))
+        };
+        self.search(near.0, near.1, point, radius_squared, visit);
+        if offset * offset <= radius_squared {
+            self.search(far.0, far.1, point, radius_squared, visit);
+        }
+    }
+}
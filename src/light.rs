@@ -0,0 +1,191 @@
+//! This module defines the [`Light`] trait, which should be implemented for light sources that
+//! can be sampled directly from a point on a surface.
+//!
+//! Sampling lights directly (next-event estimation) finds small or distant emitters that
+//! scattered rays would rarely hit by chance.
+
+/// Implement [`AreaLight`] as a [`Light`].
+mod area;
+/// Implement [`BackgroundLight`] as a [`Light`].
+mod background;
+/// Implement [`DirectionalLight`] as a [`Light`].
+mod directional;
+/// Implement [`GoniometricLight`] as a [`Light`].
+mod goniometric;
+/// Implement [`PointLight`] as a [`Light`].
+mod point;
+/// Implement [`SpotLight`] as a [`Light`].
+mod spot;
+
+/// Re-export the implemented lights.
+pub use self::{
+    area::AreaLight,
+    background::BackgroundLight,
+    directional::DirectionalLight,
+    goniometric::{GoniometricLight, IesProfile},
+    point::PointLight,
+    spot::SpotLight,
+};
+
+use crate::entity::World;
+use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
+use nalgebra as na;
+
+/// Defines the light arriving at a reference point from a sampled direction.
+pub struct LightSample {
+    /// The unit direction from the reference point towards the light.
+    pub direction: na::UnitVector3<f64>,
+    /// The distance from the reference point to the sampled point on the light, which is
+    /// infinite for lights infinitely far away.
+    pub distance: f64,
+    /// The radiance arriving at the reference point, ignoring occlusion.
+    pub radiance: na::Vector3<f64>,
+    /// The probability density of the direction, with respect to solid angle.
+    ///
+    /// For lights concentrated on a single point or direction, e.g. point lights, `radiance`
+    /// is instead the irradiance perpendicular to the direction, and `pdf` is 1.
+    pub pdf: f64,
+    /// The geometric normal at the sampled point, if the light is a surface.
+    pub normal: Option<na::UnitVector3<f64>>,
+    /// Whether scattered rays may also hit the light, in which case the sample is weighted
+    /// against scattering with multiple importance sampling.
+    pub hittable: bool,
+}
+
+/// Defines a ray of light leaving a light source, e.g. to trace paths starting from lights.
+pub struct EmissionSample {
+    /// The ray leaving the light, with a unit direction. For lights infinitely far away, the
+    /// origin lies on a disk facing the direction just outside the world.
+    pub ray: Ray,
+    /// The geometric normal at the origin, if the light is emitted from a surface.
+    pub normal: Option<na::UnitVector3<f64>>,
+    /// The radiance carried by the ray.
+    ///
+    /// For lights concentrated on a single point, this is instead the intensity, and for lights
+    /// arriving from a single direction, the irradiance perpendicular to the direction.
+    pub radiance: na::Vector3<f64>,
+    /// The probability density of the origin with respect to area, which is 1 for lights
+    /// concentrated on a single point.
+    pub pdf_position: f64,
+    /// The probability density of the direction with respect to solid angle, which is 1 for
+    /// lights arriving from a single direction.
+    pub pdf_direction: f64,
+}
+
+/// A trait that samples the light arriving at a point from a light source.
+pub trait Light: Send + Sync {
+    /// Sample a direction from the reference point towards the light, given two uniform random
+    /// numbers in [0, 1).
+    ///
+    /// Returns `None` if no light from the source arrives at the reference point.
+    fn sample(
+        &self,
+        world: &World,
+        reference: &na::Point3<f64>,
+        u: (f64, f64),
+    ) -> Option<LightSample>;
+
+    /// The probability density (with respect to solid angle) that [`Light::sample`] returns
+    /// the given direction from the reference point. Note that the direction vector is not
+    /// necessarily a unit vector.
+    ///
+    /// Lights that scattered rays never hit have no density, so the default returns 0.
+    fn pdf(
+        &self,
+        _world: &World,
+        _reference: &na::Point3<f64>,
+        _direction: &na::Vector3<f64>,
+    ) -> f64 {
+        0.
+    }
+
+    /// Sample a ray leaving the light, given two pairs of uniform random numbers in [0, 1) for
+    /// the origin and the direction respectively.
+    ///
+    /// Returns `None` if the light does not support emitting rays, which is the default.
+    fn sample_emission(
+        &self,
+        _world: &World,
+        _u: (f64, f64),
+        _v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        None
+    }
+
+    /// The probability densities of the origin (with respect to area) and the direction (with
+    /// respect to solid angle) that [`Light::sample_emission`] returns the given ray.
+    ///
+    /// The direction of the ray should be a unit vector. The default returns zeros.
+    fn pdf_emission(&self, _world: &World, _ray: &Ray) -> (f64, f64) {
+        (0., 0.)
+    }
+
+    /// Whether the light is concentrated on a single point or direction, so that rays never hit
+    /// it by chance.
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// Whether the light is infinitely far away, in which case its position is meaningless.
+    fn is_infinite(&self) -> bool {
+        false
+    }
+}
+
+/// Sample the origin of a ray entering the world from infinitely far away along the given
+/// direction, which lies on a disk facing the direction just outside the world.
+///
+/// Returns the origin and its probability density with respect to area, or `None` if the world
+/// is empty.
+fn sample_world_disk(
+    world: &World,
+    direction: &na::UnitVector3<f64>,
+    (u0, u1): (f64, f64),
+) -> Option<(na::Point3<f64>, f64)> {
+    let (center, radius) = world.bounding_sphere();
+    if radius <= 0. {
+        return None;
+    }
+    let (t1, t2) = orthonormal_basis(direction);
+    let (r, phi) = (radius * u0.sqrt(), std::f64::consts::TAU * u1);
+    let origin = center - radius * direction.into_inner() + r * (phi.cos() * t1 + phi.sin() * t2);
+    Some((origin, world_disk_pdf(world)))
+}
+
+/// The probability density (with respect to area) of [`sample_world_disk`].
+fn world_disk_pdf(world: &World) -> f64 {
+    let (_, radius) = world.bounding_sphere();
+    if radius > 0. {
+        1. / (std::f64::consts::PI * radius * radius)
+    } else {
+        0.
+    }
+}
+
+/// Sample a unit direction uniformly over the sphere, and return it with its density.
+fn sample_sphere((u0, u1): (f64, f64)) -> (na::UnitVector3<f64>, f64) {
+    let z = 1. - 2. * u0;
+    let r = (1. - z * z).max(0.).sqrt();
+    let phi = std::f64::consts::TAU * u1;
+    let direction = na::UnitVector3::new_unchecked(na::vector![r * phi.cos(), r * phi.sin(), z]);
+    (direction, 1. / (4. * std::f64::consts::PI))
+}
+
+/// Sample a unit direction uniformly within the cone around the axis, given `1 - cos(theta)`
+/// where `theta` is the half angle of the cone, and return it with its density.
+fn sample_cone(
+    axis: &na::UnitVector3<f64>,
+    one_minus_cos_max: f64,
+    (u0, u1): (f64, f64),
+) -> (na::UnitVector3<f64>, f64) {
+    let one_minus_cos = u0 * one_minus_cos_max;
+    let cos_theta = 1. - one_minus_cos;
+    let sin_theta = (one_minus_cos * (2. - one_minus_cos)).sqrt();
+    let phi = std::f64::consts::TAU * u1;
+    let (t1, t2) = orthonormal_basis(axis);
+    let direction = na::UnitVector3::new_normalize(
+        cos_theta * axis.into_inner() + sin_theta * (phi.cos() * t1 + phi.sin() * t2),
+    );
+    (direction, 1. / (std::f64::consts::TAU * one_minus_cos_max))
+}
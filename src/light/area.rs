@@ -0,0 +1,132 @@
+//! Implement the [`AreaLight`], which samples the surface of an emissive entity.
+
+use super::{EmissionSample, Light, LightSample};
+use crate::entity::{GeometryHit, Material, World};
+use crate::ray::Ray;
+use crate::utils::orthonormal_basis;
+use nalgebra as na;
+use std::f64::consts::FRAC_1_PI;
+
+/// An entity with an emissive material, sampled through [`Geometry::sample`].
+///
+/// [`Geometry::sample`]: crate::entity::Geometry::sample
+pub struct AreaLight {
+    /// The index of the entity in the world.
+    entity: usize,
+}
+
+impl AreaLight {
+    /// Create a new [`AreaLight`] for the entity of the given index in the world.
+    pub fn new(entity: usize) -> Self {
+        Self { entity }
+    }
+
+    /// Obtain the index of the entity in the world.
+    pub fn entity(&self) -> usize {
+        self.entity
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(
+        &self,
+        world: &World,
+        reference: &na::Point3<f64>,
+        u: (f64, f64),
+    ) -> Option<LightSample> {
+        let entity = &world.entities()[self.entity];
+        let sample = entity.geometry().sample(reference, u)?;
+        let ray = Ray::new(*reference, sample.hit.point - reference);
+        let (direction, distance) = na::Unit::try_new_and_get(ray.direction, 0.)?;
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: entity.material().emitted(&ray, &sample.hit),
+            pdf: sample.pdf,
+            normal: Some(sample.hit.geometric_normal),
+            hittable: true,
+        })
+    }
+
+    fn pdf(&self, world: &World, reference: &na::Point3<f64>, direction: &na::Vector3<f64>) -> f64 {
+        let entity = &world.entities()[self.entity];
+        entity.geometry().pdf(reference, direction)
+    }
+
+    fn sample_emission(
+        &self,
+        world: &World,
+        u: (f64, f64),
+        (v0, v1): (f64, f64),
+    ) -> Option<EmissionSample> {
+        let entity = &world.entities()[self.entity];
+        let sample = entity.geometry().sample_surface(u)?;
+
+        // Choose a side of the surface proportional to the light it emits, and reuse the
+        // position of `v0` within the interval of the side to sample the direction.
+        let (front, back) = side_weights(entity.material(), &sample.hit);
+        let total = front + back;
+        if total <= 0. {
+            return None;
+        }
+        let (hit, side_pdf, v0) = if v0 * total < front {
+            (sample.hit, front / total, v0 * total / front)
+        } else {
+            (flip(&sample.hit), back / total, (v0 * total - front) / back)
+        };
+
+        // Emit cosine-weighted around the normal of the side.
+        let (t1, t2) = orthonormal_basis(&hit.geometric_normal);
+        let (r, phi) = (v0.clamp(0., 1.).sqrt(), std::f64::consts::TAU * v1);
+        let cosine = (1. - r * r).max(0.).sqrt();
+        let direction =
+            cosine * hit.geometric_normal.into_inner() + r * (phi.cos() * t1 + phi.sin() * t2);
+        let radiance = entity
+            .material()
+            .emitted(&Ray::new(hit.point + direction, -direction), &hit);
+        Some(EmissionSample {
+            ray: Ray::new(hit.point, direction),
+            normal: Some(hit.geometric_normal),
+            radiance,
+            pdf_position: sample.pdf,
+            pdf_direction: side_pdf * cosine * FRAC_1_PI,
+        })
+    }
+
+    fn pdf_emission(&self, world: &World, ray: &Ray) -> (f64, f64) {
+        let entity = &world.entities()[self.entity];
+        let area = entity.geometry().area();
+        // Find the surface at the origin, facing the direction of the ray.
+        let towards = Ray::new(ray.origin + ray.direction, -ray.direction);
+        let Some(hit) = entity.geometry().hit(&towards, (1. - 1e-6, 1. + 1e-6)) else {
+            return (0., 0.);
+        };
+        let (front, back) = side_weights(entity.material(), &hit);
+        if area <= 0. || front <= 0. {
+            return (0., 0.);
+        }
+        let cosine = ray.direction.dot(&hit.geometric_normal).max(0.);
+        (1. / area, front / (front + back) * cosine * FRAC_1_PI)
+    }
+}
+
+/// Compute how much light is emitted from the side of the surface that the hit record faces,
+/// and from the opposite side.
+fn side_weights(material: &dyn Material, hit: &GeometryHit) -> (f64, f64) {
+    let weight = |hit: &GeometryHit| {
+        let normal = hit.geometric_normal.into_inner();
+        let ray = Ray::new(hit.point + normal, -normal);
+        material.emitted(&ray, hit).max().max(0.)
+    };
+    (weight(hit), weight(&flip(hit)))
+}
+
+/// Turn the hit record to the opposite side of the surface.
+fn flip(hit: &GeometryHit) -> GeometryHit {
+    GeometryHit {
+        normal: -hit.normal,
+        geometric_normal: -hit.geometric_normal,
+        exterior: !hit.exterior,
+        ..hit.clone()
+    }
+}
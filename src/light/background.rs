@@ -0,0 +1,68 @@
+//! Implement the [`BackgroundLight`], which samples the background of the world.
+
+use super::{sample_world_disk, world_disk_pdf, EmissionSample, Light, LightSample};
+use crate::entity::World;
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// The background of the world, sampled through [`Background::sample`].
+///
+/// [`Background::sample`]: crate::background::Background::sample
+pub struct BackgroundLight;
+
+impl Light for BackgroundLight {
+    fn sample(
+        &self,
+        world: &World,
+        _reference: &na::Point3<f64>,
+        u: (f64, f64),
+    ) -> Option<LightSample> {
+        let sample = world.background().sample(u)?;
+        Some(LightSample {
+            direction: sample.direction,
+            distance: f64::INFINITY,
+            radiance: sample.radiance,
+            pdf: sample.pdf,
+            normal: None,
+            hittable: true,
+        })
+    }
+
+    fn pdf(
+        &self,
+        world: &World,
+        _reference: &na::Point3<f64>,
+        direction: &na::Vector3<f64>,
+    ) -> f64 {
+        world.background().pdf(direction)
+    }
+
+    fn sample_emission(
+        &self,
+        world: &World,
+        u: (f64, f64),
+        v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        let sample = world.background().sample(u)?;
+        let direction = -sample.direction;
+        let (origin, pdf_position) = sample_world_disk(world, &direction, v)?;
+        Some(EmissionSample {
+            ray: Ray::new(origin, direction.into_inner()),
+            normal: None,
+            radiance: sample.radiance,
+            pdf_position,
+            pdf_direction: sample.pdf,
+        })
+    }
+
+    fn pdf_emission(&self, world: &World, ray: &Ray) -> (f64, f64) {
+        (
+            world_disk_pdf(world),
+            world.background().pdf(&-ray.direction),
+        )
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
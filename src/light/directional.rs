@@ -0,0 +1,120 @@
+//! Implement the [`DirectionalLight`], which models a distant light such as the sun.
+
+use super::{sample_cone, sample_world_disk, world_disk_pdf, EmissionSample, Light, LightSample};
+use crate::entity::World;
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// A light infinitely far away, arriving from (nearly) a single direction.
+///
+/// With a positive angular diameter, the light arrives uniformly from a small disk on the sky
+/// like the sun, which casts soft shadows. Either way, it is never hit by scattered rays, so
+/// it does not show up in reflections.
+pub struct DirectionalLight {
+    /// The direction the light travels to.
+    direction: na::UnitVector3<f64>,
+    /// The irradiance on three color channels, received by a surface facing the light.
+    irradiance: na::Vector3<f64>,
+    /// `1 - cos(theta)`, where `theta` is half the angular diameter.
+    one_minus_cos_max: f64,
+}
+
+impl DirectionalLight {
+    /// Create a new [`DirectionalLight`] travelling to the given direction, with the given
+    /// irradiance.
+    pub fn new(direction: na::Vector3<f64>, irradiance: na::Vector3<f64>) -> Self {
+        Self {
+            direction: na::UnitVector3::new_normalize(direction),
+            irradiance,
+            one_minus_cos_max: 0.,
+        }
+    }
+
+    /// Set the angular diameter (in radians) of the light as seen from the world, e.g. about
+    /// 0.0093 for the sun.
+    ///
+    /// Panics if the angle is negative or exceeds π.
+    pub fn with_angular_diameter(mut self, angle: f64) -> Self {
+        assert!(
+            (0. ..=std::f64::consts::PI).contains(&angle),
+            "DirectionalLight: the angular diameter should be in [0, π]."
+        );
+        // Equivalent to `1 - cos(angle / 2)`, but accurate for small angles.
+        self.one_minus_cos_max = 2. * (angle / 4.).sin().powi(2);
+        self
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(
+        &self,
+        _world: &World,
+        _reference: &na::Point3<f64>,
+        u: (f64, f64),
+    ) -> Option<LightSample> {
+        let axis = -self.direction;
+        if self.one_minus_cos_max <= 0. {
+            return Some(LightSample {
+                direction: axis,
+                distance: f64::INFINITY,
+                radiance: self.irradiance,
+                pdf: 1.,
+                normal: None,
+                hittable: false,
+            });
+        }
+
+        // Sample uniformly within the cone subtended by the disk of the light, over which the
+        // radiance is spread evenly.
+        let (direction, pdf) = sample_cone(&axis, self.one_minus_cos_max, u);
+        Some(LightSample {
+            direction,
+            distance: f64::INFINITY,
+            radiance: self.irradiance * pdf,
+            pdf,
+            normal: None,
+            hittable: false,
+        })
+    }
+
+    fn sample_emission(
+        &self,
+        world: &World,
+        u: (f64, f64),
+        v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        let (direction, radiance, pdf_direction) = if self.one_minus_cos_max <= 0. {
+            (self.direction, self.irradiance, 1.)
+        } else {
+            let (direction, pdf) = sample_cone(&self.direction, self.one_minus_cos_max, v);
+            (direction, self.irradiance * pdf, pdf)
+        };
+        let (origin, pdf_position) = sample_world_disk(world, &direction, u)?;
+        Some(EmissionSample {
+            ray: Ray::new(origin, direction.into_inner()),
+            normal: None,
+            radiance,
+            pdf_position,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, world: &World, ray: &Ray) -> (f64, f64) {
+        let cosine = ray.direction.dot(&self.direction);
+        let pdf_direction = if self.one_minus_cos_max > 0. && 1. - cosine <= self.one_minus_cos_max
+        {
+            1. / (std::f64::consts::TAU * self.one_minus_cos_max)
+        } else {
+            0.
+        };
+        (world_disk_pdf(world), pdf_direction)
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
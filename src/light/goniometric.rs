@@ -0,0 +1,236 @@
+//! Implement the [`GoniometricLight`], which emits light from a single point following a
+//! measured intensity profile, and the [`IesProfile`] it is based on.
+
+use super::{sample_sphere, EmissionSample, Light, LightSample};
+use crate::entity::World;
+use crate::ray::Ray;
+use nalgebra as na;
+use std::sync::Arc;
+
+/// The luminous intensity of a luminaire in all directions, as measured in a photometric file
+/// (IES LM-63, type C photometry).
+///
+/// In the local frame of the luminaire, the vertical angle goes from 0 at nadir (-Y) to 180
+/// degrees at zenith (+Y), and the horizontal angle goes from 0 at +X to 90 degrees at -Z.
+/// Profiles covering only part of the horizontal circle are mirrored into the full circle
+/// according to the usual symmetries.
+#[derive(Debug, Clone)]
+pub struct IesProfile {
+    /// The vertical angles in radians, in ascending order.
+    vertical: Vec<f64>,
+    /// The horizontal angles in radians, in ascending order.
+    horizontal: Vec<f64>,
+    /// The intensity in candela for each horizontal angle and then each vertical angle.
+    candela: Vec<f64>,
+    /// The maximum intensity in candela.
+    max: f64,
+}
+
+impl IesProfile {
+    /// Create a new [`IesProfile`] with the given angles in degrees, and the intensities in
+    /// candela listed for each horizontal angle and then each vertical angle.
+    ///
+    /// Panics if either list of angles is empty or not ascending, or the number of intensities
+    /// differs from the size of the grid.
+    pub fn new(vertical: Vec<f64>, horizontal: Vec<f64>, candela: Vec<f64>) -> Self {
+        for angles in [&vertical, &horizontal] {
+            assert!(
+                !angles.is_empty() && angles.windows(2).all(|w| w[0] < w[1]),
+                "IesProfile: the angles should be non-empty and strictly ascending."
+            );
+        }
+        assert_eq!(
+            candela.len(),
+            vertical.len() * horizontal.len(),
+            "IesProfile: the number of intensities should equal the size of the angle grid."
+        );
+        let max = candela.iter().copied().fold(0., f64::max);
+        Self {
+            vertical: vertical.into_iter().map(f64::to_radians).collect(),
+            horizontal: horizontal.into_iter().map(f64::to_radians).collect(),
+            candela,
+            max,
+        }
+    }
+}
+
+impl IesProfile {
+    /// The maximum intensity over all directions, in candela.
+    pub fn max_intensity(&self) -> f64 {
+        self.max
+    }
+
+    /// Compute the intensity (in candela) towards the given unit direction in the local frame
+    /// of the luminaire, with bilinear interpolation between the measured angles.
+    pub fn intensity(&self, direction: &na::UnitVector3<f64>) -> f64 {
+        use std::f64::consts::TAU;
+
+        let theta = (-direction.y).clamp(-1., 1.).acos();
+        let phi = f64::atan2(-direction.z, direction.x).rem_euclid(TAU);
+        let Some((v, tv)) = locate(&self.vertical, theta) else {
+            return 0.;
+        };
+        let Some((h, th)) = locate(&self.horizontal, self.fold(phi)) else {
+            return 0.;
+        };
+
+        let n = self.vertical.len();
+        let value = |h: usize, v: usize| self.candela[h.min(self.horizontal.len() - 1) * n + v];
+        let along = |h: usize| (1. - tv) * value(h, v) + tv * value(h, (v + 1).min(n - 1));
+        (1. - th) * along(h) + th * along(h + 1)
+    }
+
+    /// Mirror the horizontal angle into the range covered by the profile.
+    fn fold(&self, phi: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        let first = self.horizontal[0];
+        let last = *self.horizontal.last().unwrap();
+        if self.horizontal.len() == 1 {
+            // Symmetric around the vertical axis.
+            first
+        } else if (last - PI / 2.).abs() < 1e-6 {
+            // Symmetric in each quadrant.
+            let phi = phi % PI;
+            if phi > PI / 2. {
+                PI - phi
+            } else {
+                phi
+            }
+        } else if (last - PI).abs() < 1e-6 {
+            // Symmetric about the plane through 0 and 180 degrees.
+            if phi > PI {
+                2. * PI - phi
+            } else {
+                phi
+            }
+        } else if (first - PI / 2.).abs() < 1e-6 && (last - 1.5 * PI).abs() < 1e-6 {
+            // Symmetric about the plane through 90 and 270 degrees.
+            if phi < first {
+                PI - phi
+            } else if phi > last {
+                3. * PI - phi
+            } else {
+                phi
+            }
+        } else {
+            phi
+        }
+    }
+}
+
+/// Find the interval of the ascending angles that contains `x`, and the relative position of
+/// `x` within it. Returns `None` if `x` is outside the angles.
+fn locate(angles: &[f64], x: f64) -> Option<(usize, f64)> {
+    let (first, last) = (angles[0], *angles.last().unwrap());
+    if angles.len() == 1 {
+        return Some((0, 0.));
+    }
+    if x < first - 1e-9 || x > last + 1e-9 {
+        return None;
+    }
+    let index = angles
+        .partition_point(|&a| a <= x)
+        .clamp(1, angles.len() - 1)
+        - 1;
+    let t = (x - angles[index]) / (angles[index + 1] - angles[index]);
+    Some((index, t.clamp(0., 1.)))
+}
+
+/// An infinitely small light whose intensity varies by direction following an [`IesProfile`].
+///
+/// Like [`PointLight`], it is never hit by scattered rays.
+///
+/// [`PointLight`]: super::PointLight
+pub struct GoniometricLight {
+    /// The position of the light.
+    position: na::Point3<f64>,
+    /// The measured intensity profile.
+    profile: Arc<IesProfile>,
+    /// The radiant intensity on three color channels along the brightest direction.
+    intensity: na::Vector3<f64>,
+    /// The rotation from the local frame of the luminaire to the world.
+    rotation: na::Rotation3<f64>,
+}
+
+impl GoniometricLight {
+    /// Create a new [`GoniometricLight`] at the given position, following the profile.
+    ///
+    /// The profile is normalized such that the brightest direction has the given intensity.
+    /// By default, the nadir of the luminaire points to -Y.
+    pub fn new(
+        position: na::Point3<f64>,
+        profile: Arc<IesProfile>,
+        intensity: na::Vector3<f64>,
+    ) -> Self {
+        Self {
+            position,
+            profile,
+            intensity,
+            rotation: na::Rotation3::identity(),
+        }
+    }
+
+    /// Set the rotation from the local frame of the luminaire to the world.
+    pub fn with_rotation(mut self, rotation: na::Rotation3<f64>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+}
+
+impl Light for GoniometricLight {
+    fn sample(
+        &self,
+        _world: &World,
+        reference: &na::Point3<f64>,
+        _u: (f64, f64),
+    ) -> Option<LightSample> {
+        let (direction, distance) = na::Unit::try_new_and_get(self.position - reference, 0.)?;
+        let max = self.profile.max_intensity();
+        if max <= 0. {
+            return None;
+        }
+        let local = self.rotation.inverse() * -direction;
+        let scale = self.profile.intensity(&local) / max;
+        (scale > 0.).then(|| LightSample {
+            direction,
+            distance,
+            radiance: scale * self.intensity / (distance * distance),
+            pdf: 1.,
+            normal: None,
+            hittable: false,
+        })
+    }
+
+    fn sample_emission(
+        &self,
+        _world: &World,
+        _u: (f64, f64),
+        v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        let max = self.profile.max_intensity();
+        if max <= 0. {
+            return None;
+        }
+        let (direction, pdf_direction) = sample_sphere(v);
+        let scale = self
+            .profile
+            .intensity(&(self.rotation.inverse() * direction))
+            / max;
+        (scale > 0.).then(|| EmissionSample {
+            ray: Ray::new(self.position, direction.into_inner()),
+            normal: None,
+            radiance: scale * self.intensity,
+            pdf_position: 1.,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, _world: &World, _ray: &Ray) -> (f64, f64) {
+        (1., 1. / (4. * std::f64::consts::PI))
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,69 @@
+//! Implement the [`PointLight`], which emits light from a single point.
+
+use super::{sample_sphere, EmissionSample, Light, LightSample};
+use crate::entity::World;
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// An infinitely small light emitting uniformly in all directions.
+///
+/// The light falls off with the squared distance, and is never hit by scattered rays.
+pub struct PointLight {
+    /// The position of the light.
+    position: na::Point3<f64>,
+    /// The radiant intensity on three color channels, i.e. the power per unit solid angle.
+    intensity: na::Vector3<f64>,
+}
+
+impl PointLight {
+    /// Create a new [`PointLight`] at the given position with the given intensity.
+    pub fn new(position: na::Point3<f64>, intensity: na::Vector3<f64>) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(
+        &self,
+        _world: &World,
+        reference: &na::Point3<f64>,
+        _u: (f64, f64),
+    ) -> Option<LightSample> {
+        let (direction, distance) = na::Unit::try_new_and_get(self.position - reference, 0.)?;
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity / (distance * distance),
+            pdf: 1.,
+            normal: None,
+            hittable: false,
+        })
+    }
+
+    fn sample_emission(
+        &self,
+        _world: &World,
+        _u: (f64, f64),
+        v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        let (direction, pdf_direction) = sample_sphere(v);
+        Some(EmissionSample {
+            ray: Ray::new(self.position, direction.into_inner()),
+            normal: None,
+            radiance: self.intensity,
+            pdf_position: 1.,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, _world: &World, _ray: &Ray) -> (f64, f64) {
+        (1., 1. / (4. * std::f64::consts::PI))
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
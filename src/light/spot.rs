@@ -0,0 +1,126 @@
+//! Implement the [`SpotLight`], which emits a cone of light from a single point.
+
+use super::{sample_cone, EmissionSample, Light, LightSample};
+use crate::entity::World;
+use crate::ray::Ray;
+use nalgebra as na;
+
+/// An infinitely small light emitting within a cone around its direction.
+///
+/// The intensity is full inside the inner cone, and falls off smoothly to zero at the outer
+/// cone. Like [`PointLight`], it is never hit by scattered rays.
+///
+/// [`PointLight`]: super::PointLight
+pub struct SpotLight {
+    /// The position of the light.
+    position: na::Point3<f64>,
+    /// The direction the cone points to.
+    direction: na::UnitVector3<f64>,
+    /// The radiant intensity on three color channels along the direction.
+    intensity: na::Vector3<f64>,
+    /// The cosine of the half angle of the inner cone.
+    cos_inner: f64,
+    /// The cosine of the half angle of the outer cone.
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    /// Create a new [`SpotLight`] at the given position, pointing to the given direction, with
+    /// the given intensity.
+    ///
+    /// The inner and outer cones have half angles of 30 and 45 degrees by default.
+    pub fn new(
+        position: na::Point3<f64>,
+        direction: na::Vector3<f64>,
+        intensity: na::Vector3<f64>,
+    ) -> Self {
+        use std::f64::consts::{FRAC_PI_4, FRAC_PI_6};
+
+        Self {
+            position,
+            direction: na::UnitVector3::new_normalize(direction),
+            intensity,
+            cos_inner: FRAC_PI_6.cos(),
+            cos_outer: FRAC_PI_4.cos(),
+        }
+    }
+
+    /// Set the half angles (in radians) of the inner and outer cones.
+    ///
+    /// Panics if the inner angle exceeds the outer angle, or the outer angle exceeds π.
+    pub fn with_cone(mut self, inner: f64, outer: f64) -> Self {
+        assert!(
+            0. <= inner && inner <= outer && outer <= std::f64::consts::PI,
+            "SpotLight: the cone angles should satisfy 0 <= inner <= outer <= π."
+        );
+        self.cos_inner = inner.cos();
+        self.cos_outer = outer.cos();
+        self
+    }
+}
+
+impl SpotLight {
+    /// The fraction of the intensity emitted along the given unit direction.
+    fn falloff(&self, direction: &na::UnitVector3<f64>) -> f64 {
+        let cosine = direction.dot(&self.direction);
+        if cosine >= self.cos_inner {
+            return 1.;
+        }
+        if cosine <= self.cos_outer {
+            return 0.;
+        }
+        // Smoothstep between the outer and inner cones.
+        let x = (cosine - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        x * x * (3. - 2. * x)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(
+        &self,
+        _world: &World,
+        reference: &na::Point3<f64>,
+        _u: (f64, f64),
+    ) -> Option<LightSample> {
+        let (direction, distance) = na::Unit::try_new_and_get(self.position - reference, 0.)?;
+        let falloff = self.falloff(&-direction);
+        (falloff > 0.).then(|| LightSample {
+            direction,
+            distance,
+            radiance: falloff * self.intensity / (distance * distance),
+            pdf: 1.,
+            normal: None,
+            hittable: false,
+        })
+    }
+
+    fn sample_emission(
+        &self,
+        _world: &World,
+        _u: (f64, f64),
+        v: (f64, f64),
+    ) -> Option<EmissionSample> {
+        // Emit uniformly within the outer cone, outside which there is no light.
+        let (direction, pdf_direction) = sample_cone(&self.direction, 1. - self.cos_outer, v);
+        let falloff = self.falloff(&direction);
+        (falloff > 0.).then(|| EmissionSample {
+            ray: Ray::new(self.position, direction.into_inner()),
+            normal: None,
+            radiance: falloff * self.intensity,
+            pdf_position: 1.,
+            pdf_direction,
+        })
+    }
+
+    fn pdf_emission(&self, _world: &World, ray: &Ray) -> (f64, f64) {
+        let one_minus_cos_max = 1. - self.cos_outer;
+        if one_minus_cos_max <= 0. || ray.direction.dot(&self.direction) < self.cos_outer {
+            return (1., 0.);
+        }
+        (1., 1. / (std::f64::consts::TAU * one_minus_cos_max))
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,154 @@
+//! This module loads entities (and light profiles) from external file formats.
+//! Please refer to [`obj`], [`mtl`], [`gltf`] and [`ies`] for more details.
+
+/// Import glTF 2.0 scenes.
+mod gltf;
+/// Load IES LM-63 photometric files.
+mod ies;
+/// Load Wavefront MTL material libraries.
+mod mtl;
+/// Load Wavefront OBJ files.
+mod obj;
+
+/// Re-export the loaders.
+pub use self::{
+    gltf::{load_gltf, GltfScene},
+    ies::load_ies,
+    obj::load_obj,
+};
+
+use std::path::{Path, PathBuf};
+
+/// Defines the errors that may occur when loading a file.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file cannot be read.
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+    /// The file is malformed.
+    Parse {
+        /// The path of the file.
+        path: PathBuf,
+        /// The line number (starting from 1) where the error occurs.
+        line: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// The glTF file or one of its resources cannot be loaded.
+    Gltf {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying glTF error.
+        source: ::gltf::Error,
+    },
+    /// An image referred to by the file cannot be loaded.
+    Image {
+        /// The path of the image.
+        path: PathBuf,
+        /// The underlying image error.
+        source: image::ImageError,
+    },
+    /// The file is well-formed, but its content cannot be used.
+    Invalid {
+        /// The path of the file.
+        path: PathBuf,
+        /// Description of the error.
+        message: String,
+    },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            Self::Gltf { path, source } => write!(f, "failed to load {}: {source}", path.display()),
+            Self::Image { path, source } => {
+                write!(f, "failed to load image {}: {source}", path.display())
+            }
+            Self::Invalid { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Gltf { source, .. } => Some(source),
+            Self::Image { source, .. } => Some(source),
+            Self::Parse { .. } | Self::Invalid { .. } => None,
+        }
+    }
+}
+
+/// Read the whole file into a string.
+fn read_to_string(path: &Path) -> Result<String, LoadError> {
+    std::fs::read_to_string(path).map_err(|source| LoadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// The location of a line in a text file, used to report parse errors.
+#[derive(Clone, Copy)]
+struct Location<'a> {
+    /// The path of the file.
+    path: &'a Path,
+    /// The line number, starting from 1.
+    line: usize,
+}
+
+impl Location<'_> {
+    /// Create a parse error at this location.
+    fn error(&self, message: impl Into<String>) -> LoadError {
+        LoadError::Parse {
+            path: self.path.to_path_buf(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    /// Parse a floating point number.
+    fn parse_f64(&self, token: &str) -> Result<f64, LoadError> {
+        token
+            .parse()
+            .map_err(|_| self.error(format!("invalid number `{token}`")))
+    }
+
+    /// Parse exactly `N` floating point numbers.
+    fn parse_floats<const N: usize>(&self, tokens: &[&str]) -> Result<[f64; N], LoadError> {
+        if tokens.len() != N {
+            return Err(self.error(format!("expected {N} numbers, found {}", tokens.len())));
+        }
+        let mut values = [0.; N];
+        for (value, token) in values.iter_mut().zip(tokens) {
+            *value = self.parse_f64(token)?;
+        }
+        Ok(values)
+    }
+}
+
+/// Iterate over the meaningful lines of a text file, with comments and blank lines removed.
+///
+/// Each item consists of the location of the line, the keyword, and the remaining tokens.
+fn statements<'a>(
+    path: &'a Path,
+    source: &'a str,
+) -> impl Iterator<Item = (Location<'a>, &'a str, Vec<&'a str>)> {
+    source.lines().enumerate().filter_map(move |(i, line)| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next()?;
+        let location = Location { path, line: i + 1 };
+        Some((location, keyword, tokens.collect()))
+    })
+}
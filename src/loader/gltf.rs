@@ -0,0 +1,323 @@
+//! Import glTF 2.0 scenes (`.gltf` or `.glb`) into [`Entity`]s and [`CameraBuilder`]s.
+
+use super::LoadError;
+use crate::camera::CameraBuilder;
+use crate::entity::{
+    Dielectric, DiffuseLight, Entity, ImageTexture, Lambertian, Material, Metal, SolidColor,
+    Texture, TriangleMesh, WrapMode,
+};
+use nalgebra as na;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The content of a glTF scene.
+pub struct GltfScene {
+    /// All mesh primitives in the scene, with their node transforms applied.
+    pub entities: Vec<Entity>,
+    /// All perspective cameras in the scene, in the order they are found in the node hierarchy.
+    ///
+    /// The aspect ratio is set if the file specifies it, while the image size and rendering
+    /// quality are left for the caller to set.
+    pub cameras: Vec<CameraBuilder>,
+}
+
+impl GltfScene {
+    /// Refractive index used when a transmissive material does not specify one.
+    const DEFAULT_IOR: f64 = 1.5;
+}
+
+/// Load the default scene (or the first scene if there is no default one) of a glTF file.
+///
+/// Each triangle primitive becomes an [`Entity`], whose material is mapped from the PBR
+/// metallic-roughness parameters:
+/// - Materials whose emissive factor (scaled by `KHR_materials_emissive_strength`) is at least as
+///   bright as the base color factor become [`DiffuseLight`], with the emission as radiance.
+///   Materials cannot both emit and reflect light, so fainter emission is ignored.
+/// - Materials with `KHR_materials_transmission` become [`Dielectric`], with the base color as
+///   albedo and the refractive index from `KHR_materials_ior`.
+/// - Metallic materials become [`Metal`], using the roughness as fuzz.
+/// - Others become [`Lambertian`] with the base color as albedo.
+///
+/// The base color is the base color texture tinted by the base color factor, or the factor
+/// alone if the material has no texture. The texture is mapped with the set of texture
+/// coordinates that it names. Points, lines and orthographic cameras are ignored.
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<GltfScene, LoadError> {
+    let path = path.as_ref();
+    let (document, buffers, images) = gltf::import(path).map_err(|source| LoadError::Gltf {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| LoadError::Invalid {
+            path: path.to_path_buf(),
+            message: "no scene in file".into(),
+        })?;
+
+    let mut loaded = GltfScene {
+        entities: Vec::new(),
+        cameras: Vec::new(),
+    };
+    let mut textures = HashMap::new();
+    let mut stack: Vec<_> = scene
+        .nodes()
+        .map(|node| (node, na::Matrix4::identity()))
+        .collect();
+    while let Some((node, parent)) = stack.pop() {
+        let local: na::Matrix4<f32> = node.transform().matrix().into();
+        let transform = parent * local.cast::<f64>();
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                let invalid = |message: String| LoadError::Invalid {
+                    path: path.to_path_buf(),
+                    message: format!(
+                        "mesh {} primitive {}: {message}",
+                        mesh.index(),
+                        primitive.index()
+                    ),
+                };
+                let material = primitive.material();
+                let uv_set = material
+                    .pbr_metallic_roughness()
+                    .base_color_texture()
+                    .map_or(0, |info| info.tex_coord());
+                if let Some(geometry) =
+                    load_primitive(&primitive, &transform, &buffers, uv_set).map_err(invalid)?
+                {
+                    let albedo = base_color(&material, &images, &mut textures);
+                    let material = to_material(&material, albedo);
+                    loaded
+                        .entities
+                        .push(Entity::new(Box::new(geometry), material));
+                }
+            }
+        }
+        if let Some(camera) = node.camera() {
+            loaded.cameras.extend(to_camera(&camera, &transform));
+        }
+        stack.extend(node.children().map(|child| (child, transform)));
+    }
+    Ok(loaded)
+}
+
+/// Load a primitive as a [`TriangleMesh`] in world space, with the texture coordinates of the
+/// given set.
+///
+/// Returns `Ok(None)` if the primitive does not consist of triangles.
+fn load_primitive(
+    primitive: &gltf::Primitive,
+    transform: &na::Matrix4<f64>,
+    buffers: &[gltf::buffer::Data],
+    uv_set: u32,
+) -> Result<Option<TriangleMesh>, String> {
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions: Vec<na::Point3<f64>> = reader
+        .read_positions()
+        .ok_or("missing vertex positions")?
+        .map(|p| transform.transform_point(&na::Point3::from(p).cast()))
+        .collect();
+
+    let indices: Vec<usize> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+        None => (0..positions.len()).collect(),
+    };
+    if let Some(i) = indices.iter().find(|&&i| i >= positions.len()) {
+        return Err(format!("vertex index {i} out of range"));
+    }
+    let mut triangles: Vec<[usize; 3]> = match primitive.mode() {
+        gltf::mesh::Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+        gltf::mesh::Mode::TriangleStrip => (2..indices.len())
+            .map(|i| match i % 2 {
+                0 => [indices[i - 2], indices[i - 1], indices[i]],
+                _ => [indices[i - 1], indices[i - 2], indices[i]],
+            })
+            .collect(),
+        gltf::mesh::Mode::TriangleFan => (2..indices.len())
+            .map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect(),
+        _ => return Ok(None),
+    };
+    if triangles.is_empty() {
+        return Ok(None);
+    }
+
+    // A mirroring transform reverses the winding order, which should be kept counter-clockwise.
+    let linear = transform.fixed_view::<3, 3>(0, 0).into_owned();
+    if linear.determinant() < 0. {
+        triangles.iter_mut().for_each(|t| t.swap(1, 2));
+    }
+
+    let mut mesh = TriangleMesh::new(positions, triangles);
+    if let Some(normals) = reader.read_normals() {
+        let normal_matrix = linear.try_inverse().unwrap_or(linear).transpose();
+        let normals: Option<Vec<_>> = normals
+            .map(|n| {
+                let n = normal_matrix * na::Vector3::from(n).cast::<f64>();
+                na::UnitVector3::try_new(n, f64::EPSILON)
+            })
+            .collect();
+        if let Some(normals) = normals.filter(|n| n.len() == mesh.positions().len()) {
+            mesh = mesh.with_normals(normals);
+        }
+    }
+    if let Some(uvs) = reader.read_tex_coords(uv_set) {
+        // glTF places the origin of texture coordinates at the top-left corner of the image,
+        // while the meshes place it at the bottom-left corner.
+        let uvs: Vec<_> = uvs
+            .into_f32()
+            .map(|[u, v]| na::point![u as f64, 1. - v as f64])
+            .collect();
+        if uvs.len() == mesh.positions().len() {
+            mesh = mesh.with_uvs(uvs);
+        }
+    }
+    Ok(Some(mesh))
+}
+
+/// Map a glTF PBR material onto a [`Material`].
+///
+/// `albedo` should be the base color computed by [`base_color`].
+fn to_material(material: &gltf::Material, albedo: Arc<dyn Texture>) -> Box<dyn Material> {
+    let pbr = material.pbr_metallic_roughness();
+
+    // Faint emission, e.g. a tint, is ignored rather than hiding the reflection of the base.
+    let strength = material.emissive_strength().unwrap_or(1.) as f64;
+    let emission = na::Vector3::from(material.emissive_factor()).cast::<f64>() * strength;
+    let [r, g, b, _] = pbr.base_color_factor();
+    if emission.max() > 0. && emission.max() >= r.max(g).max(b) as f64 {
+        let light = DiffuseLight::new(emission);
+        return match material.double_sided() {
+            true => Box::new(light.two_sided()),
+            false => Box::new(light),
+        };
+    }
+
+    let transmission = material
+        .transmission()
+        .map_or(0., |t| t.transmission_factor());
+    if transmission > 0.5 {
+        let ior = material.ior().map_or(GltfScene::DEFAULT_IOR, f64::from);
+        Box::new(Dielectric::from_texture(albedo, ior))
+    } else if pbr.metallic_factor() > 0.5 {
+        Box::new(Metal::from_texture(albedo, pbr.roughness_factor() as f64))
+    } else {
+        Box::new(Lambertian::from_texture(albedo))
+    }
+}
+
+/// Compute the base color of a material as a texture.
+///
+/// Images are converted at most once, and cached in `textures` by their indices.
+fn base_color(
+    material: &gltf::Material,
+    images: &[gltf::image::Data],
+    textures: &mut HashMap<usize, Option<ImageTexture>>,
+) -> Arc<dyn Texture> {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, _] = pbr.base_color_factor();
+    let factor = na::vector![r, g, b].cast::<f64>();
+
+    let texture = pbr.base_color_texture().and_then(|info| {
+        let texture = info.texture();
+        let index = texture.source().index();
+        let image = textures
+            .entry(index)
+            .or_insert_with(|| to_image(&images[index]).map(ImageTexture::new))
+            .clone()?;
+        let sampler = texture.sampler();
+        Some(
+            image
+                .with_wrap(to_wrap(sampler.wrap_s()), to_wrap(sampler.wrap_t()))
+                .with_tint(factor),
+        )
+    });
+    match texture {
+        Some(texture) => Arc::new(texture),
+        None => Arc::new(SolidColor::new(factor)),
+    }
+}
+
+/// Convert decoded glTF image data into an [`image::DynamicImage`].
+///
+/// Returns `None` if the pixel data does not match the size of the image.
+fn to_image(data: &gltf::image::Data) -> Option<image::DynamicImage> {
+    use gltf::image::Format;
+    use image::{DynamicImage, ImageBuffer};
+
+    let (width, height) = (data.width, data.height);
+    let bytes = || data.pixels.clone();
+    let words = || {
+        data.pixels
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect::<Vec<_>>()
+    };
+    let floats = || {
+        data.pixels
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>()
+    };
+    Some(match data.format {
+        Format::R8 => DynamicImage::ImageLuma8(ImageBuffer::from_raw(width, height, bytes())?),
+        Format::R8G8 => DynamicImage::ImageLumaA8(ImageBuffer::from_raw(width, height, bytes())?),
+        Format::R8G8B8 => DynamicImage::ImageRgb8(ImageBuffer::from_raw(width, height, bytes())?),
+        Format::R8G8B8A8 => {
+            DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, bytes())?)
+        }
+        Format::R16 => DynamicImage::ImageLuma16(ImageBuffer::from_raw(width, height, words())?),
+        Format::R16G16 => {
+            DynamicImage::ImageLumaA16(ImageBuffer::from_raw(width, height, words())?)
+        }
+        Format::R16G16B16 => {
+            DynamicImage::ImageRgb16(ImageBuffer::from_raw(width, height, words())?)
+        }
+        Format::R16G16B16A16 => {
+            DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, words())?)
+        }
+        Format::R32G32B32FLOAT => {
+            DynamicImage::ImageRgb32F(ImageBuffer::from_raw(width, height, floats())?)
+        }
+        Format::R32G32B32A32FLOAT => {
+            DynamicImage::ImageRgba32F(ImageBuffer::from_raw(width, height, floats())?)
+        }
+    })
+}
+
+/// Convert a glTF wrapping mode into a [`WrapMode`].
+fn to_wrap(mode: gltf::texture::WrappingMode) -> WrapMode {
+    match mode {
+        gltf::texture::WrappingMode::ClampToEdge => WrapMode::Clamp,
+        gltf::texture::WrappingMode::MirroredRepeat => WrapMode::Mirror,
+        gltf::texture::WrappingMode::Repeat => WrapMode::Repeat,
+    }
+}
+
+/// Convert a glTF camera into a [`CameraBuilder`], placed by the world transform of its node.
+///
+/// Returns `None` for orthographic cameras, which are not supported.
+fn to_camera(camera: &gltf::Camera, transform: &na::Matrix4<f64>) -> Option<CameraBuilder> {
+    let gltf::camera::Projection::Perspective(perspective) = camera.projection() else {
+        return None;
+    };
+    // A glTF camera looks towards its local -Z axis, with +Y pointing up.
+    let position = transform.transform_point(&na::Point3::origin());
+    let forward = transform.transform_vector(&-na::Vector3::z());
+    let up = transform.transform_vector(&na::Vector3::y());
+
+    let builder = CameraBuilder::new()
+        .look_from(position)
+        .look_at(position + forward)
+        .up(up)
+        .view_angle(perspective.yfov() as f64);
+    Some(match perspective.aspect_ratio() {
+        Some(ratio) => builder.ratio(ratio as f64),
+        None => builder,
+    })
+}
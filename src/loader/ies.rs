@@ -0,0 +1,213 @@
+//! Parse IES LM-63 photometric files into [`IesProfile`]s.
+//!
+//! Reference: ANSI/IES LM-63-19, "Approved Method: IES Standard File Format for the Electronic
+//! Transfer of Photometric Data and Related Information".
+
+use super::{read_to_string, LoadError, Location};
+use crate::light::IesProfile;
+use std::path::Path;
+
+/// Load the intensity profile of a luminaire from an IES LM-63 file.
+///
+/// Only type C photometry, which is used by almost all architectural luminaires, is supported.
+/// Tilt data is read but ignored, i.e. the luminaire is assumed to be mounted as measured.
+pub fn load_ies(path: impl AsRef<Path>) -> Result<IesProfile, LoadError> {
+    let path = path.as_ref();
+    parse_ies(path, &read_to_string(path)?)
+}
+
+/// Parse the source of an IES file at the given path, which is only used in errors.
+fn parse_ies(path: &Path, source: &str) -> Result<IesProfile, LoadError> {
+    // The header consists of keyword lines, and ends with the `TILT=` line.
+    let mut lines = source.lines().enumerate();
+    let tilt = loop {
+        let Some((_, line)) = lines.next() else {
+            return Err(LoadError::Invalid {
+                path: path.to_path_buf(),
+                message: "missing `TILT=` line".into(),
+            });
+        };
+        if let Some(tilt) = line.trim().strip_prefix("TILT=") {
+            break tilt.trim();
+        }
+    };
+
+    // The rest of the file is a stream of numbers, regardless of line breaks.
+    let mut numbers = Numbers {
+        tokens: lines
+            .flat_map(|(i, line)| {
+                let location = Location { path, line: i + 1 };
+                line.split_whitespace().map(move |token| (location, token))
+            })
+            .collect(),
+        position: 0,
+        path,
+    };
+
+    if tilt == "INCLUDE" {
+        let _geometry = numbers.next("lamp-to-luminaire geometry")?;
+        let count = numbers.next_count("number of tilt angles")?;
+        for _ in 0..2 * count {
+            numbers.next("tilt data")?;
+        }
+    }
+
+    let _lamps = numbers.next("number of lamps")?;
+    let _lumens = numbers.next("lumens per lamp")?;
+    let multiplier = numbers.next("candela multiplier")?;
+    let vertical_count = numbers.next_count("number of vertical angles")?;
+    let horizontal_count = numbers.next_count("number of horizontal angles")?;
+    let photometric_type = numbers.next("photometric type")?;
+    let _units = numbers.next("units type")?;
+    let _dimensions = [
+        numbers.next("luminaire width")?,
+        numbers.next("luminaire length")?,
+        numbers.next("luminaire height")?,
+    ];
+    let ballast = numbers.next("ballast factor")?;
+    let _reserved = numbers.next("ballast-lamp photometric factor")?;
+    let _watts = numbers.next("input watts")?;
+
+    let invalid = |message: &str| LoadError::Invalid {
+        path: path.to_path_buf(),
+        message: message.into(),
+    };
+    if photometric_type != 1. {
+        return Err(invalid("only type C photometry is supported"));
+    }
+    if vertical_count == 0 || horizontal_count == 0 {
+        return Err(invalid("the numbers of angles should be positive"));
+    }
+
+    let vertical = numbers.take(vertical_count, "vertical angle")?;
+    let horizontal = numbers.take(horizontal_count, "horizontal angle")?;
+    for angles in [&vertical, &horizontal] {
+        if angles.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(invalid("the angles should be strictly ascending"));
+        }
+    }
+    let candela = numbers.take(vertical_count * horizontal_count, "candela value")?;
+    let scale = multiplier * ballast;
+    Ok(IesProfile::new(
+        vertical,
+        horizontal,
+        candela.into_iter().map(|c| c * scale).collect(),
+    ))
+}
+
+/// A stream of numbers following the header of an IES file.
+struct Numbers<'a> {
+    /// All tokens with their locations.
+    tokens: Vec<(Location<'a>, &'a str)>,
+    /// The index of the next token.
+    position: usize,
+    /// The path of the file.
+    path: &'a Path,
+}
+
+impl Numbers<'_> {
+    /// Parse the next number, described by `what` in error messages.
+    fn next(&mut self, what: &str) -> Result<f64, LoadError> {
+        let Some(&(location, token)) = self.tokens.get(self.position) else {
+            let line = self.tokens.last().map_or(1, |(location, _)| location.line);
+            let location = Location {
+                path: self.path,
+                line,
+            };
+            return Err(location.error(format!("unexpected end of file, expected {what}")));
+        };
+        self.position += 1;
+        location.parse_f64(token)
+    }
+
+    /// Parse the next number as a count, which should be a non-negative integer.
+    fn next_count(&mut self, what: &str) -> Result<usize, LoadError> {
+        let value = self.next(what)?;
+        if value < 0. || value.fract() != 0. {
+            let (location, token) = self.tokens[self.position - 1];
+            return Err(location.error(format!("invalid {what} `{token}`")));
+        }
+        Ok(value as usize)
+    }
+
+    /// Parse the next `count` numbers.
+    fn take(&mut self, count: usize, what: &str) -> Result<Vec<f64>, LoadError> {
+        (0..count).map(|_| self.next(what)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra as na;
+
+    /// Parse the source of an IES file, as if it were in the temporary directory.
+    fn parse(source: &str) -> Result<IesProfile, LoadError> {
+        parse_ies(&std::env::temp_dir().join("rayst-test.ies"), source)
+    }
+
+    /// The intensity straight down, i.e. at the vertical angle of 0.
+    fn intensity_down(profile: &IesProfile) -> f64 {
+        profile.intensity(&-na::Vector3::y_axis())
+    }
+
+    #[test]
+    fn minimal_file() {
+        let source = "\
+IESNA:LM-63-2002
+[TEST] minimal
+TILT=NONE
+1 1000 4 3 1 1 2 0.5 0.5 0
+0.25 1 100
+0 45 90
+0
+100 80
+50
+";
+        // The candela values are scaled by the multiplier and the ballast factor.
+        let profile = parse(source).unwrap();
+        assert_eq!(profile.max_intensity(), 100.);
+        assert_eq!(intensity_down(&profile), 100.);
+    }
+
+    #[test]
+    fn included_tilt() {
+        let source = "\
+IESNA:LM-63-2002
+TILT=INCLUDE
+1
+3
+0 45 90
+1 0.9 0.8
+1 1000 1 2 1 1 2 0.5 0.5 0
+1 1 100
+0 90
+0
+30 10
+";
+        let profile = parse(source).unwrap();
+        assert_eq!(intensity_down(&profile), 30.);
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(matches!(
+            parse("IESNA:LM-63-2002\n[TEST] no tilt\n"),
+            Err(LoadError::Invalid { .. })
+        ));
+        // Type A photometry.
+        assert!(matches!(
+            parse("TILT=NONE\n1 1000 1 1 1 3 2 0 0 0\n1 1 100\n0\n0\n10\n"),
+            Err(LoadError::Invalid { .. })
+        ));
+        // The file ends in the middle of the candela values.
+        assert!(matches!(
+            parse("TILT=NONE\n1 1000 1 2 1 1 2 0 0 0\n1 1 100\n0 90\n0\n10\n"),
+            Err(LoadError::Parse { line: 6, .. })
+        ));
+        assert!(matches!(
+            parse("TILT=NONE\n1 1000 1 -2 1 1 2 0 0 0\n"),
+            Err(LoadError::Parse { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+//! Parse Wavefront MTL material libraries, and map their parameters onto [`Material`]s.
+
+use super::{read_to_string, statements, LoadError};
+use crate::entity::{Dielectric, DiffuseLight, ImageTexture, Lambertian, Material, Metal};
+use nalgebra as na;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The parameters of a material in an MTL file.
+///
+/// Only the parameters that can be represented by the materials of this project are kept.
+#[derive(Debug, Clone)]
+pub struct MtlMaterial {
+    /// Diffuse color (`Kd`).
+    pub diffuse: na::Vector3<f64>,
+    /// Specular color (`Ks`).
+    pub specular: na::Vector3<f64>,
+    /// Emissive color (`Ke`).
+    pub emission: na::Vector3<f64>,
+    /// Specular exponent (`Ns`), in [0, 1000].
+    pub shininess: f64,
+    /// Refractive index (`Ni`).
+    pub ri: Option<f64>,
+    /// Transmission filter (`Tf`).
+    pub transmission: Option<na::Vector3<f64>>,
+    /// Opacity (`d`, or `1 - Tr`).
+    pub opacity: f64,
+    /// Illumination model (`illum`).
+    pub illum: u32,
+    /// Diffuse texture map (`map_Kd`), resolved against the directory of the MTL file.
+    pub diffuse_map: Option<PathBuf>,
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        Self {
+            diffuse: na::vector![0.8, 0.8, 0.8],
+            specular: na::vector![0., 0., 0.],
+            emission: na::vector![0., 0., 0.],
+            shininess: 0.,
+            ri: None,
+            transmission: None,
+            opacity: 1.,
+            illum: 2,
+            diffuse_map: None,
+        }
+    }
+}
+
+impl MtlMaterial {
+    /// Refractive index used when a transparent material does not specify `Ni`.
+    const DEFAULT_RI: f64 = 1.5;
+}
+
+impl MtlMaterial {
+    /// Whether the material is transparent, either by opacity or by illumination model.
+    pub fn is_transparent(&self) -> bool {
+        self.opacity < 1. || matches!(self.illum, 4 | 6 | 7 | 9)
+    }
+
+    /// Whether the material is dominated by specular reflection.
+    pub fn is_specular(&self) -> bool {
+        self.specular.max() > 0. && self.specular.max() >= self.diffuse.max()
+    }
+
+    /// Map the parameters onto a [`Material`].
+    ///
+    /// - Emissive materials become [`DiffuseLight`], with `Ke` as radiance.
+    /// - Transparent materials become [`Dielectric`], with `Tf` (or white) as albedo and `Ni` as
+    ///   refractive index.
+    /// - Materials dominated by `Ks` become [`Metal`], whose fuzz is derived from `Ns`.
+    /// - Others become [`Lambertian`] with `Kd` as albedo, or with `diffuse_map` tinted by `Kd`
+    ///   if `map_Kd` is given.
+    ///
+    /// `diffuse_map` should be the texture loaded from [`MtlMaterial::diffuse_map`].
+    pub fn to_material(&self, diffuse_map: Option<&ImageTexture>) -> Box<dyn Material> {
+        if self.emission.max() > 0. {
+            Box::new(DiffuseLight::new(self.emission))
+        } else if self.is_transparent() {
+            let albedo = self.transmission.unwrap_or(na::vector![1., 1., 1.]);
+            Box::new(Dielectric::new(albedo, self.ri.unwrap_or(Self::DEFAULT_RI)))
+        } else if self.is_specular() {
+            // Convert the Phong exponent into a roughness, as in the Beckmann distribution.
+            let fuzz = (2. / (self.shininess + 2.)).sqrt();
+            Box::new(Metal::new(self.specular, fuzz))
+        } else if let Some(texture) = diffuse_map {
+            let texture = texture.clone().with_tint(self.diffuse);
+            Box::new(Lambertian::from_texture(Arc::new(texture)))
+        } else {
+            Box::new(Lambertian::new(self.diffuse))
+        }
+    }
+}
+
+/// Load all materials in an MTL file, indexed by their names.
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, MtlMaterial>, LoadError> {
+    let source = read_to_string(path)?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+    for (location, keyword, args) in statements(path, &source) {
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(location.error("missing material name"));
+            }
+            materials.extend(current.take());
+            current = Some((args.join(" "), MtlMaterial::default()));
+            continue;
+        }
+        let Some((_, material)) = current.as_mut() else {
+            return Err(location.error(format!("`{keyword}` before any `newmtl`")));
+        };
+        match keyword {
+            "Kd" => material.diffuse = location.parse_floats::<3>(&args)?.into(),
+            "Ks" => material.specular = location.parse_floats::<3>(&args)?.into(),
+            "Ke" => material.emission = location.parse_floats::<3>(&args)?.into(),
+            "Tf" => material.transmission = Some(location.parse_floats::<3>(&args)?.into()),
+            "Ns" => material.shininess = location.parse_floats::<1>(&args)?[0],
+            "Ni" => material.ri = Some(location.parse_floats::<1>(&args)?[0]),
+            "d" => material.opacity = location.parse_floats::<1>(&args)?[0],
+            "Tr" => material.opacity = 1. - location.parse_floats::<1>(&args)?[0],
+            "illum" => {
+                material.illum = match args.as_slice() {
+                    [token] => token
+                        .parse()
+                        .map_err(|_| location.error(format!("invalid illumination `{token}`")))?,
+                    _ => return Err(location.error("expected 1 illumination model")),
+                }
+            }
+            "map_Kd" => {
+                // Options such as `-s 1 1 1` precede the file name.
+                let file = args
+                    .last()
+                    .ok_or_else(|| location.error("missing texture file name"))?;
+                material.diffuse_map = Some(directory.join(file));
+            }
+            // Other statements (e.g. `Ka`, `map_Bump`) are not supported and thus ignored.
+            _ => {}
+        }
+    }
+    materials.extend(current);
+    Ok(materials)
+}
@@ -0,0 +1,391 @@
+//! Parse Wavefront OBJ files into [`Entity`]s made of [`TriangleMesh`]es.
+
+use super::mtl::{load_mtl, MtlMaterial};
+use super::{read_to_string, statements, LoadError, Location};
+use crate::entity::{Entity, ImageTexture, TriangleMesh};
+use nalgebra as na;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A corner of a face, which refers to a position, and optionally a texture coordinate and a
+/// normal. All indices are zero-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// A group of triangles sharing the same material.
+#[derive(Default)]
+struct Group {
+    /// The name of the material, or `None` for the default material, which is also used if no
+    /// library defines the name.
+    material: Option<String>,
+    /// The triangles in the group.
+    triangles: Vec<[Corner; 3]>,
+}
+
+/// The vertex data shared by all groups in an OBJ file.
+#[derive(Default)]
+struct Vertices {
+    positions: Vec<na::Point3<f64>>,
+    uvs: Vec<na::Point2<f64>>,
+    normals: Vec<na::Vector3<f64>>,
+}
+
+/// The content of an OBJ file.
+struct ObjFile {
+    /// The vertex data shared by all groups.
+    vertices: Vertices,
+    /// The groups with at least one triangle.
+    groups: Vec<Group>,
+    /// The materials of the MTL libraries referred to by the file.
+    materials: HashMap<String, MtlMaterial>,
+}
+
+/// Load an OBJ file into entities.
+///
+/// Every object, group or material change (`o`, `g`, `usemtl`) starts a new [`TriangleMesh`],
+/// and each mesh becomes an [`Entity`] with the material mapped from the MTL libraries referred
+/// to by `mtllib`. Please refer to [`MtlMaterial::to_material`] for the mapping of materials.
+/// Polygons are triangulated as fans.
+///
+/// Exporters often refer to materials that are not defined, or to libraries that do not exist,
+/// so such meshes fall back to the default material, as do meshes without `usemtl`.
+///
+/// Texture coordinates and normals are only kept for a mesh if all of its corners have them, so
+/// a group mixing corners with and without them silently loses them.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<Vec<Entity>, LoadError> {
+    let path = path.as_ref();
+    let source = read_to_string(path)?;
+    let ObjFile {
+        vertices,
+        groups,
+        materials,
+    } = parse_obj(path, &source)?;
+
+    let default_material = MtlMaterial::default();
+    let mut textures = HashMap::new();
+    groups
+        .iter()
+        .map(|group| {
+            let material = group
+                .material
+                .as_ref()
+                .and_then(|name| materials.get(name))
+                .unwrap_or(&default_material);
+            let diffuse_map = match &material.diffuse_map {
+                Some(path) => Some(load_texture(path, &mut textures)?),
+                None => None,
+            };
+            Ok(Entity::new(
+                Box::new(build_mesh(group, &vertices)),
+                material.to_material(diffuse_map),
+            ))
+        })
+        .collect()
+}
+
+/// Parse the source of an OBJ file at the given path, which MTL libraries are relative to.
+fn parse_obj(path: &Path, source: &str) -> Result<ObjFile, LoadError> {
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut vertices = Vertices::default();
+    let mut materials = HashMap::new();
+    let mut groups = Vec::new();
+    let mut current = Group::default();
+
+    for (location, keyword, args) in statements(path, source) {
+        match keyword {
+            "v" => {
+                // The optional fourth component is the weight, and some exporters append
+                // vertex colors. Neither of them is supported.
+                if args.len() < 3 {
+                    return Err(location.error("expected at least 3 coordinates"));
+                }
+                let [x, y, z] = location.parse_floats(&args[..3])?;
+                vertices.positions.push(na::point![x, y, z]);
+            }
+            "vt" => {
+                if args.is_empty() || args.len() > 3 {
+                    return Err(location.error("expected 1 to 3 texture coordinates"));
+                }
+                let u = location.parse_f64(args[0])?;
+                let v = args.get(1).map_or(Ok(0.), |v| location.parse_f64(v))?;
+                vertices.uvs.push(na::point![u, v]);
+            }
+            "vn" => vertices
+                .normals
+                .push(location.parse_floats::<3>(&args)?.into()),
+            "f" => {
+                if args.len() < 3 {
+                    return Err(location.error("a face should have at least 3 vertices"));
+                }
+                let corners = args
+                    .iter()
+                    .map(|token| parse_corner(&location, token, &vertices))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for i in 1..corners.len() - 1 {
+                    current
+                        .triangles
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            "o" | "g" => {
+                let material = current.material.clone();
+                groups.push(std::mem::take(&mut current));
+                current.material = material;
+            }
+            "usemtl" => {
+                if args.is_empty() {
+                    return Err(location.error("missing material name"));
+                }
+                groups.push(std::mem::take(&mut current));
+                current.material = Some(args.join(" "));
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(location.error("missing material library file name"));
+                }
+                for file in args {
+                    match load_mtl(&directory.join(file)) {
+                        Ok(library) => materials.extend(library),
+                        Err(LoadError::Io { source, .. })
+                            if source.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+            // Other statements (e.g. `s`, `l`, `vp`) are not supported and thus ignored.
+            _ => {}
+        }
+    }
+    groups.push(current);
+    groups.retain(|group| !group.triangles.is_empty());
+    Ok(ObjFile {
+        vertices,
+        groups,
+        materials,
+    })
+}
+
+/// Load an image texture, reusing the textures that have already been loaded.
+fn load_texture<'a>(
+    path: &Path,
+    textures: &'a mut HashMap<PathBuf, ImageTexture>,
+) -> Result<&'a ImageTexture, LoadError> {
+    if !textures.contains_key(path) {
+        let texture = ImageTexture::open(path).map_err(|source| LoadError::Image {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        textures.insert(path.to_path_buf(), texture);
+    }
+    Ok(&textures[path])
+}
+
+/// Parse a face corner in the form of `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_corner(
+    location: &Location,
+    token: &str,
+    vertices: &Vertices,
+) -> Result<Corner, LoadError> {
+    let mut parts = token.split('/');
+    let mut index = |count: usize| -> Result<Option<usize>, LoadError> {
+        match parts.next() {
+            None | Some("") => Ok(None),
+            Some(part) => resolve_index(location, part, count).map(Some),
+        }
+    };
+    let position = index(vertices.positions.len())?
+        .ok_or_else(|| location.error(format!("missing vertex index in `{token}`")))?;
+    let uv = index(vertices.uvs.len())?;
+    let normal = index(vertices.normals.len())?;
+    if parts.next().is_some() {
+        return Err(location.error(format!("invalid face vertex `{token}`")));
+    }
+    Ok(Corner {
+        position,
+        uv,
+        normal,
+    })
+}
+
+/// Convert a one-based (or negative, relative) OBJ index into a zero-based index, given the
+/// number of elements defined so far.
+fn resolve_index(location: &Location, token: &str, count: usize) -> Result<usize, LoadError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| location.error(format!("invalid index `{token}`")))?;
+    let resolved = match index {
+        1.. => Some(index as usize - 1),
+        ..0 => count.checked_sub(index.unsigned_abs() as usize),
+        0 => None,
+    };
+    resolved
+        .filter(|&i| i < count)
+        .ok_or_else(|| location.error(format!("index {index} out of range")))
+}
+
+/// Build a [`TriangleMesh`] from the triangles of a group.
+///
+/// Corners referring to the same vertex data are merged. Texture coordinates and normals are
+/// only kept if every corner of the group has them (and every normal can be normalized), so
+/// a single corner without them drops them for the whole group.
+fn build_mesh(group: &Group, vertices: &Vertices) -> TriangleMesh {
+    let mut corners: HashMap<Corner, usize> = HashMap::new();
+    let mut unique = Vec::new();
+    let indices = group
+        .triangles
+        .iter()
+        .map(|triangle| {
+            triangle.map(|corner| {
+                *corners.entry(corner).or_insert_with(|| {
+                    unique.push(corner);
+                    unique.len() - 1
+                })
+            })
+        })
+        .collect();
+
+    let positions = unique
+        .iter()
+        .map(|corner| vertices.positions[corner.position])
+        .collect();
+    let mut mesh = TriangleMesh::new(positions, indices);
+
+    let uvs: Option<Vec<_>> = unique
+        .iter()
+        .map(|corner| Some(vertices.uvs[corner.uv?]))
+        .collect();
+    if let Some(uvs) = uvs {
+        mesh = mesh.with_uvs(uvs);
+    }
+
+    let normals: Option<Vec<_>> = unique
+        .iter()
+        .map(|corner| na::UnitVector3::try_new(vertices.normals[corner.normal?], f64::EPSILON))
+        .collect();
+    if let Some(normals) = normals {
+        mesh = mesh.with_normals(normals);
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse the source of an OBJ file, as if it were in the temporary directory.
+    fn parse(source: &str) -> Result<ObjFile, LoadError> {
+        parse_obj(&std::env::temp_dir().join("rayst-test.obj"), source)
+    }
+
+    #[test]
+    fn corners_with_uvs_and_normals() {
+        let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 0 1
+vn 0 0 2
+f 1/1/1 2/2/1 3/3/1
+";
+        let ObjFile {
+            vertices, groups, ..
+        } = parse(source).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].triangles[0][1],
+            Corner {
+                position: 1,
+                uv: Some(1),
+                normal: Some(0),
+            }
+        );
+
+        let mesh = build_mesh(&groups[0], &vertices);
+        assert_eq!(mesh.indices(), &[[0, 1, 2]]);
+        assert_eq!(mesh.uvs().unwrap()[2], na::point![0., 1.]);
+        assert_eq!(*mesh.normals().unwrap()[0], na::vector![0., 0., 1.]);
+    }
+
+    #[test]
+    fn corners_without_uvs_or_normals() {
+        let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+f 1//1 2//1 3//1
+f 1 2 3
+";
+        let ObjFile {
+            vertices, groups, ..
+        } = parse(source).unwrap();
+        let triangles = &groups[0].triangles;
+        assert_eq!(triangles[0][0].uv, None);
+        assert_eq!(triangles[0][0].normal, Some(0));
+        assert_eq!(triangles[1][0].normal, None);
+
+        // The second face lacks normals, so the group drops them.
+        let mesh = build_mesh(&groups[0], &vertices);
+        assert!(mesh.uvs().is_none());
+        assert!(mesh.normals().is_none());
+    }
+
+    #[test]
+    fn negative_indices() {
+        let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+vt 0.5 0.5
+f -4/-1 -3/-1 -2/-1 -1/-1
+";
+        let groups = parse(source).unwrap().groups;
+        let positions: Vec<_> = groups[0]
+            .triangles
+            .iter()
+            .map(|triangle| triangle.map(|corner| corner.position))
+            .collect();
+        assert_eq!(positions, [[0, 1, 2], [0, 2, 3]]);
+        assert!(groups[0].triangles[1]
+            .iter()
+            .all(|corner| corner.uv == Some(0)));
+
+        assert!(matches!(
+            parse("v 0 0 0\nf -2 1 1\n"),
+            Err(LoadError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("v 0 0 0\nf 0 1 1\n"),
+            Err(LoadError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn missing_mtllib() {
+        let source = "\
+mtllib rayst-missing.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl red
+f 1 2 3
+";
+        // The material is kept by name, but no library defines it.
+        let ObjFile {
+            groups, materials, ..
+        } = parse(source).unwrap();
+        assert_eq!(groups[0].material.as_deref(), Some("red"));
+        assert!(materials.is_empty());
+
+        let result = parse("usemtl\n");
+        assert!(matches!(result, Err(LoadError::Parse { line: 1, .. })));
+    }
+}
@@ -1,24 +1,52 @@
 //! This project aims to implement a basic rendering algorithm in pure rust.
 //! Reference: <https://raytracing.github.io/books/RayTracingInOneWeekend.html>
 
+/// Defines the light arriving from infinitely far away.
+pub mod background;
 /// Defines the configuration of camera.
 pub mod camera;
+/// Defines piecewise-constant distributions for importance sampling.
+pub mod distribution;
 /// Defines entities in the world.
 pub mod entity;
+/// Defines the algorithms that compute the light arriving at the camera.
+pub mod integrator;
+/// Defines light sources sampled directly from surfaces.
+pub mod light;
+/// Loads entities from external files.
+pub mod loader;
 /// Defines the ray.
 pub mod ray;
+/// Defines the generators of random numbers for rendering.
+pub mod sampler;
 /// Some useful tools.
 pub mod utils;
 
-use crate::entity::{Dielectric, Entity, Lambertian, Material, Metal, Sphere};
+use crate::entity::{Dielectric, Entity, Lambertian, Material, Metal, Sphere, World};
+use crate::integrator::{
+    AmbientOcclusion, AovIntegrator, BidirectionalPathTracer, Integrator, MetropolisLightTransport,
+    PhotonMapper,
+};
+use crate::sampler::SamplerKind;
 use nalgebra as na;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 fn main() {
     // Set Camera.
-    // Note: You can change the sampling rate, image size to adjust the quality of rendering.
+    // Note: You can change the sampling rate, image size to adjust the quality of rendering, or
+    // set a time budget, e.g. `.time_budget(std::time::Duration::from_secs(600))`, to render for
+    // a fixed time instead.
+    // Pass the name of a sampler (e.g. `sobol`) after the integrator to change the sampler.
+    let sampler = std::env::args()
+        .nth(2)
+        .map_or(SamplerKind::default(), |name| {
+            name.parse().expect("Failed to parse sampler")
+        });
     let cam = camera::CameraBuilder::new()
         .sampling(500)
+        .snapshot_interval(std::time::Duration::from_secs(30))
+        .sampler(sampler)
         .image_width(1200)
         .ratio(16. / 9.)
         .look_from(na::point![13., 2., 3.])
@@ -28,7 +56,7 @@ fn main() {
         .build();
 
     // Set World.
-    let mut world = vec![
+    let mut entities = vec![
         // Ground
         Entity::new(
             Box::new(Sphere::new(1000., na::point![0., -1000., 0.])),
@@ -48,8 +76,8 @@ fn main() {
         ),
     ];
 
-    let mut rng = rand::thread_rng();
-    // Randomly generate spheres.
+    // Randomly generate spheres, from the seed of the camera so that the image is reproducible.
+    let mut rng = StdRng::seed_from_u64(cam.seed());
     // Note: You can turn down the number of entities to improve performance.
     for a in -11..11 {
         for b in -11..11 {
@@ -82,18 +110,44 @@ fn main() {
                     // Dielectric
                     Box::new(Dielectric::new(na::vector![1., 1., 1.], 1.5))
                 };
-                world.push(Entity::new(Box::new(Sphere::new(0.2, center)), material));
+                entities.push(Entity::new(Box::new(Sphere::new(0.2, center)), material));
             }
         }
     }
 
-    // Render and Show.
+    // Build the acceleration structure over all entities.
+    let world = World::new(entities);
+
+    // Choose the integrator. Pass `bdpt` for bidirectional path tracing, `photon` for photon
+    // mapping, `mlt` for Metropolis light transport, `ao` for a clay preview, or the name of an
+    // AOV (e.g. `normal`) to inspect the scene.
+    let integrator: Box<dyn Integrator> = match std::env::args().nth(1) {
+        Some(name) if name == "bdpt" => Box::new(BidirectionalPathTracer::new()),
+        Some(name) if name == "photon" => Box::new(PhotonMapper::new()),
+        Some(name) if name == "mlt" => {
+            Box::new(MetropolisLightTransport::new().with_path_tracer(cam.path_tracer()))
+        }
+        Some(name) if name == "ao" => Box::new(AmbientOcclusion::new(2.)),
+        Some(aov) => Box::new(AovIntegrator::new(
+            aov.parse().expect("Failed to parse AOV"),
+        )),
+        None => Box::new(cam.path_tracer()),
+    };
+
+    // Render and Show, saving the image rendered so far at every snapshot.
+    let save = |buffer: &na::DVector<f64>| {
+        // Write to a temporary file first, so an interrupted save keeps the previous image.
+        let image = utils::into_image(buffer.iter().cloned(), cam.width(), cam.height());
+        image
+            .save("image/image.tmp.png")
+            .expect("Failed to save image");
+        std::fs::rename("image/image.tmp.png", "image/image.png").expect("Failed to save image");
+    };
     let start_time = std::time::Instant::now();
-    let buffer = cam.render_world(&world);
+    let buffer = cam.render_world_progressive(&world, integrator.as_ref(), save);
     let end_time = std::time::Instant::now();
 
     println!("Render time: {:.2?}", end_time - start_time);
 
-    let image = utils::into_image(buffer.iter().cloned(), cam.width(), cam.height());
-    image.save("image/image.png").expect("Failed to save image");
+    save(&buffer);
 }
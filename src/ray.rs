@@ -11,6 +11,12 @@ pub struct Ray {
     pub direction: na::Vector3<f64>,
 }
 
+impl Ray {
+    /// The smallest parameter `t` accepted for rays leaving a surface, which keeps the ray from
+    /// hitting the surface it starts from due to rounding errors.
+    pub const T_MIN: f64 = 1e-9;
+}
+
 impl Ray {
     /// Create a new ray with the given origin and direction.
     pub fn new(origin: na::Point3<f64>, direction: na::Vector3<f64>) -> Self {
@@ -0,0 +1,143 @@
+//! This module defines the [`Sampler`] trait, which provides all random numbers used while
+//! rendering, so that the numbers can be controlled (e.g. replayed) from outside.
+
+/// Implement [`HaltonSampler`] as a [`Sampler`].
+mod halton;
+/// Implement [`IndependentSampler`] as a [`Sampler`].
+mod independent;
+/// Implement [`SobolSampler`] as a [`Sampler`].
+mod sobol;
+/// Implement [`StratifiedSampler`] as a [`Sampler`].
+mod stratified;
+
+/// Re-export the implemented samplers.
+pub use self::{
+    halton::HaltonSampler, independent::IndependentSampler, sobol::SobolSampler,
+    stratified::StratifiedSampler,
+};
+
+/// A trait that generates the random numbers used to render an image.
+///
+/// Every random decision while rendering, e.g. the position of a sample in a pixel or the
+/// direction of a scattered ray, draws its numbers from a sampler rather than a global random
+/// generator. Numbers are drawn one dimension after another, so samplers may distribute the
+/// numbers of the same dimension evenly over the samples of a pixel.
+pub trait Sampler {
+    /// Start drawing the numbers of the sample of the given index in the pixel, from the first
+    /// dimension.
+    ///
+    /// The numbers drawn afterwards should only depend on the pixel, the index and the seed of
+    /// the sampler, so that images do not depend on the order in which samples are rendered.
+    /// The default implementation ignores the sample, and keeps drawing the same stream.
+    fn start_pixel_sample(&mut self, _pixel: (u32, u32), _index: usize) {}
+
+    /// Draw a number in [0, 1) for the next dimension.
+    fn next_1d(&mut self) -> f64;
+
+    /// Draw a pair of numbers in [0, 1)² for the next two dimensions, e.g. to sample a point on
+    /// a surface.
+    fn next_2d(&mut self) -> (f64, f64) {
+        (self.next_1d(), self.next_1d())
+    }
+}
+
+/// Defines the kinds of samplers that a camera renders with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SamplerKind {
+    /// Independent uniform random numbers, see [`IndependentSampler`].
+    #[default]
+    Independent,
+    /// Jittered strata in each dimension, see [`StratifiedSampler`].
+    Stratified,
+    /// The scrambled Halton sequence, see [`HaltonSampler`].
+    Halton,
+    /// The scrambled Sobol sequence, see [`SobolSampler`].
+    Sobol,
+}
+
+impl SamplerKind {
+    /// Create a sampler of this kind for rendering the given number of samples per pixel, whose
+    /// numbers are scrambled by the given seed.
+    pub fn create(self, samples: usize, seed: u64) -> Box<dyn Sampler> {
+        match self {
+            Self::Independent => Box::new(IndependentSampler::new(seed)),
+            Self::Stratified => Box::new(StratifiedSampler::new(samples, seed)),
+            Self::Halton => Box::new(HaltonSampler::new(seed)),
+            Self::Sobol => Box::new(SobolSampler::new(samples, seed)),
+        }
+    }
+}
+
+impl std::str::FromStr for SamplerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "independent" => Ok(Self::Independent),
+            "stratified" => Ok(Self::Stratified),
+            "halton" => Ok(Self::Halton),
+            "sobol" => Ok(Self::Sobol),
+            _ => Err(format!("Unknown sampler: {s}")),
+        }
+    }
+}
+
+/// Mix the bits of the value, so that similar values give unrelated results.
+///
+/// Reference: the finalizer of MurmurHash3, with the constants of David Stafford's Mix13.
+fn mix_bits(mut v: u64) -> u64 {
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+    v
+}
+
+/// Hash a list of values into a single value.
+fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e3779b97f4a7c15, |h, &v| {
+        mix_bits(h ^ v.wrapping_add(0x9e3779b97f4a7c15))
+    })
+}
+
+/// Convert the hash into a number in [0, 1).
+fn hash_to_unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 * (1. / (1u64 << 53) as f64)
+}
+
+/// Find the element at index `i` of a random permutation of `0..n`, chosen by the seed, without
+/// building the permutation.
+///
+/// Reference: Kensler, "Correlated Multi-Jittered Sampling", 2013.
+fn permutation_element(mut i: u32, n: u32, seed: u32) -> u32 {
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < n {
+            return (i.wrapping_add(seed)) % n;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Implement the [`HaltonSampler`], which draws the samples of each pixel from a scrambled
+//! Halton sequence.
+
+use super::{hash, hash_to_unit, mix_bits, permutation_element, Sampler};
+
+/// A sampler that draws the samples of a pixel from the Halton sequence, whose dimensions are
+/// radical inverses in the successive prime bases.
+///
+/// The digits of the sequence are randomly permuted (Owen scrambling) for each pixel and
+/// dimension, so pixels are uncorrelated while each keeps its low discrepancy. Dimensions
+/// beyond the supported bases draw independent random numbers.
+pub struct HaltonSampler {
+    /// The seed that scrambles the digits.
+    seed: u64,
+    /// The current pixel.
+    pixel: (u32, u32),
+    /// The index of the current sample in the pixel.
+    index: usize,
+    /// The next dimension to draw.
+    dimension: u64,
+}
+
+impl HaltonSampler {
+    /// The prime bases of the supported dimensions.
+    const PRIMES: [u64; 64] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+        97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181,
+        191, 193, 197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281,
+        283, 293, 307, 311,
+    ];
+}
+
+impl HaltonSampler {
+    /// Create a new [`HaltonSampler`], scrambling the digits by the seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let (x, y) = (self.pixel.0 as u64, self.pixel.1 as u64);
+        let dimension = self.dimension;
+        self.dimension += 1;
+        let Some(&base) = Self::PRIMES.get(dimension as usize) else {
+            return hash_to_unit(hash(&[x, y, dimension, self.index as u64, self.seed]));
+        };
+        let scramble = hash(&[x, y, dimension, self.seed]);
+        scrambled_radical_inverse(base, self.index as u64, scramble)
+    }
+}
+
+/// Compute the radical inverse of `a` in the base, i.e. mirror its digits around the radix
+/// point, with each digit permuted randomly depending on the digits before it.
+///
+/// Digits are computed until they are smaller than 2^-32, which is precise enough to sample
+/// and much faster for small bases than full precision.
+///
+/// Reference: Pharr et al., "Physically Based Rendering", 4th edition, section 8.6.
+fn scrambled_radical_inverse(base: u64, mut a: u64, scramble: u64) -> f64 {
+    let inverse_base = 1. / base as f64;
+    let mut inverse_base_m = 1.;
+    let mut reversed = 0;
+    // Keep going after the digits of `a` run out, as the zeros are scrambled too.
+    while inverse_base_m >= 1. / (1u64 << 32) as f64 {
+        let next = a / base;
+        let digit = (a - next * base) as u32;
+        let digit_scramble = mix_bits(scramble ^ reversed) as u32;
+        let digit = permutation_element(digit, base as u32, digit_scramble) as u64;
+        reversed = reversed * base + digit;
+        inverse_base_m *= inverse_base;
+        a = next;
+    }
+    (inverse_base_m * reversed as f64).min(1. - f64::EPSILON)
+}
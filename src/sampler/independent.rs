@@ -0,0 +1,37 @@
+//! Implement the [`IndependentSampler`], which draws independent uniform random numbers.
+
+use super::{hash, Sampler};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A sampler that draws every number independently and uniformly at random.
+///
+/// The random generator is reseeded at the start of each sample of a pixel, so the numbers of
+/// a sample only depend on the seed, the pixel and the index of the sample.
+pub struct IndependentSampler {
+    /// The seed of all random generators.
+    seed: u64,
+    /// The random generator of the current sample.
+    rng: StdRng,
+}
+
+impl IndependentSampler {
+    /// Create a new [`IndependentSampler`] whose numbers are determined by the seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: usize) {
+        let (x, y) = (pixel.0 as u64, pixel.1 as u64);
+        self.rng = StdRng::seed_from_u64(hash(&[x, y, index as u64, self.seed]));
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        self.rng.gen()
+    }
+}
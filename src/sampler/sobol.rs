@@ -0,0 +1,118 @@
+//! Implement the [`SobolSampler`], which draws the samples of each pixel from a scrambled Sobol
+//! sequence.
+
+use super::{hash, permutation_element, Sampler};
+
+/// A sampler that draws each pair of dimensions of the samples of a pixel from the first two
+/// dimensions of the Sobol sequence, which are well distributed in [0, 1)² for any power of
+/// two of samples.
+///
+/// Each pair of dimensions is independently scrambled (Owen scrambling), and the order of the
+/// samples is shuffled, so that different dimensions stay uncorrelated ("padding"). This works
+/// best when the number of samples per pixel is a power of two.
+pub struct SobolSampler {
+    /// The number of samples per pixel.
+    samples: usize,
+    /// The seed that scrambles the sequence.
+    seed: u64,
+    /// The current pixel.
+    pixel: (u32, u32),
+    /// The index of the current sample in the pixel.
+    index: usize,
+    /// The next dimension to draw.
+    dimension: u64,
+}
+
+impl SobolSampler {
+    /// Create a new [`SobolSampler`] for the given number of samples per pixel, scrambling the
+    /// sequence by the seed.
+    ///
+    /// Panics if `samples` is 0.
+    pub fn new(samples: usize, seed: u64) -> Self {
+        assert!(samples > 0, "SobolSampler: `samples` should be positive.");
+        Self {
+            samples,
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// Find the index into the sequence of the current sample in the current dimension, and
+    /// the hash that scrambles it.
+    fn shuffle(&self) -> (u32, u64) {
+        let (x, y) = (self.pixel.0 as u64, self.pixel.1 as u64);
+        let scramble = hash(&[x, y, self.dimension, self.seed]);
+        let index = if self.index < self.samples {
+            permutation_element(self.index as u32, self.samples as u32, scramble as u32)
+        } else {
+            // Samples beyond the expected count are not shuffled.
+            self.index as u32
+        };
+        (index, scramble)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let (index, scramble) = self.shuffle();
+        self.dimension += 1;
+        let (x, _) = sobol(index);
+        to_unit(owen_scramble(x, (scramble >> 32) as u32))
+    }
+
+    fn next_2d(&mut self) -> (f64, f64) {
+        let (index, scramble) = self.shuffle();
+        self.dimension += 2;
+        let (x, y) = sobol(index);
+        (
+            to_unit(owen_scramble(x, (scramble >> 32) as u32)),
+            to_unit(owen_scramble(y, scramble as u32)),
+        )
+    }
+}
+
+/// Compute the first two dimensions of the point of the given index in the Sobol sequence, as
+/// binary fractions of 32 bits.
+///
+/// Reference: Kollig and Keller, "Efficient Multidimensional Sampling", 2002.
+fn sobol(mut index: u32) -> (u32, u32) {
+    let (mut x, mut y) = (0, 0);
+    let (mut bit, mut direction) = (1u32 << 31, 1u32 << 31);
+    while index != 0 {
+        if index & 1 != 0 {
+            x ^= bit;
+            y ^= direction;
+        }
+        index >>= 1;
+        bit >>= 1;
+        direction ^= direction >> 1;
+    }
+    (x, y)
+}
+
+/// Randomly permute the binary digits of the fraction, where each digit is flipped depending
+/// on the digits before it.
+///
+/// Reference: Burley, "Practical Hash-based Owen Scrambling", JCGT 2020.
+fn owen_scramble(value: u32, seed: u32) -> u32 {
+    let mut v = value.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x05526c56);
+    v ^= v.wrapping_mul(0x53a22864);
+    v.reverse_bits()
+}
+
+/// Convert a binary fraction of 32 bits into a number in [0, 1).
+fn to_unit(value: u32) -> f64 {
+    value as f64 / (1u64 << 32) as f64
+}
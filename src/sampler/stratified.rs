@@ -0,0 +1,89 @@
+//! Implement the [`StratifiedSampler`], which jitters the samples of a pixel within strata.
+
+use super::{hash, hash_to_unit, permutation_element, Sampler};
+
+/// A sampler that divides each dimension into as many strata as there are samples per pixel,
+/// and places each sample of a pixel in a different stratum, jittered within it.
+///
+/// Pairs of dimensions are divided into a grid as square as possible, so that points sampled
+/// on a surface are evenly spread over it. The strata are shuffled independently for each
+/// pixel and dimension, which keeps the dimensions uncorrelated.
+pub struct StratifiedSampler {
+    /// The number of samples per pixel, i.e. the number of strata.
+    samples: usize,
+    /// The number of columns of the grid of strata of pairs of dimensions.
+    width: usize,
+    /// The seed that shuffles the strata.
+    seed: u64,
+    /// The current pixel.
+    pixel: (u32, u32),
+    /// The index of the current sample in the pixel.
+    index: usize,
+    /// The next dimension to draw.
+    dimension: u64,
+}
+
+impl StratifiedSampler {
+    /// Create a new [`StratifiedSampler`] for the given number of samples per pixel, shuffling
+    /// the strata by the seed.
+    ///
+    /// Panics if `samples` is 0.
+    pub fn new(samples: usize, seed: u64) -> Self {
+        assert!(
+            samples > 0,
+            "StratifiedSampler: `samples` should be positive."
+        );
+        // Divide the square into the grid of the largest width not exceeding its height.
+        let width = (1..=samples.isqrt())
+            .rev()
+            .find(|&w| samples.is_multiple_of(w))
+            .unwrap();
+        Self {
+            samples,
+            width,
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// Find the stratum of the current sample in the current dimension, out of `n` strata.
+    fn stratum(&self, n: usize) -> usize {
+        let (x, y) = (self.pixel.0 as u64, self.pixel.1 as u64);
+        let shuffle = hash(&[x, y, self.dimension, self.seed]);
+        permutation_element((self.index % n) as u32, n as u32, shuffle as u32) as usize
+    }
+
+    /// Draw a random jitter in [0, 1) for the current sample in the given dimension.
+    fn jitter(&self, dimension: u64) -> f64 {
+        let (x, y) = (self.pixel.0 as u64, self.pixel.1 as u64);
+        hash_to_unit(hash(&[x, y, dimension, self.index as u64, self.seed]))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: usize) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let stratum = self.stratum(self.samples);
+        let jitter = self.jitter(self.dimension);
+        self.dimension += 1;
+        (stratum as f64 + jitter) / self.samples as f64
+    }
+
+    fn next_2d(&mut self) -> (f64, f64) {
+        let (width, height) = (self.width, self.samples / self.width);
+        let stratum = self.stratum(self.samples);
+        let jitter = (self.jitter(self.dimension), self.jitter(self.dimension + 1));
+        self.dimension += 2;
+        (
+            ((stratum % width) as f64 + jitter.0) / width as f64,
+            ((stratum / width) as f64 + jitter.1) / height as f64,
+        )
+    }
+}
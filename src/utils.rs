@@ -1,30 +1,59 @@
 //! Some utility functions for the project.
 
 use nalgebra as na;
-use rand::Rng;
-
-/// Generate a unit vector, randomly distributed on S(2).
-pub fn random_unit_vector() -> na::UnitVector3<f64> {
-    let mut rng = rand::thread_rng();
-    let z = rng.gen_range(-1.0..=1.0);
-    let r = f64::sqrt(1.0 - z * z);
-    let theta = rng.gen_range(0.0..std::f64::consts::TAU);
+
+/// Map two uniform random numbers in [0, 1) to a unit vector, uniformly distributed on S(2).
+pub fn sample_unit_vector((u0, u1): (f64, f64)) -> na::UnitVector3<f64> {
+    let z = 1. - 2. * u0;
+    let r = f64::sqrt((1.0 - z * z).max(0.));
+    let theta = std::f64::consts::TAU * u1;
     na::UnitVector3::new_unchecked(na::vector![r * theta.cos(), r * theta.sin(), z])
 }
 
-/// Generate a random point on the unit disk.
-pub fn random_in_unit_disk() -> (f64, f64) {
-    let mut rng = rand::thread_rng();
-    let r = f64::sqrt(rng.gen_range(0.0..1.0));
-    let theta = rng.gen_range(0.0..std::f64::consts::TAU);
+/// Map two uniform random numbers in [0, 1) to a point, uniformly distributed on the unit disk.
+pub fn sample_unit_disk((u0, u1): (f64, f64)) -> (f64, f64) {
+    let r = u0.sqrt();
+    let theta = std::f64::consts::TAU * u1;
     (r * theta.cos(), r * theta.sin())
 }
 
+/// Compute two unit vectors that form an orthonormal basis together with the given unit vector.
+///
+/// Reference: Duff et al., "Building an Orthonormal Basis, Revisited", JCGT 2017.
+pub fn orthonormal_basis(n: &na::UnitVector3<f64>) -> (na::Vector3<f64>, na::Vector3<f64>) {
+    let sign = 1f64.copysign(n.z);
+    let a = -1. / (sign + n.z);
+    let b = n.x * n.y * a;
+    (
+        na::vector![1. + sign * n.x * n.x * a, sign * b, -sign * n.x],
+        na::vector![b, sign + n.y * n.y * a, -n.y],
+    )
+}
+
+/// Compute the luminance of a linear RGB color.
+#[inline(always)]
+pub fn luminance(color: &na::Vector3<f64>) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+/// Compute the weight of a sample by the power heuristic of multiple importance sampling,
+/// where `pdf` is the density of the strategy that drew the sample, and `other` is the density
+/// of the other strategy.
+#[inline(always)]
+pub fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0. {
+        a / (a + b)
+    } else {
+        0.
+    }
+}
+
 /// Judge whether the vector is near to zero.
 /// The threshold can be adjusted according to need.
 #[inline(always)]
 pub fn near_zero(vector: na::Vector3<f64>) -> bool {
-    vector.data.as_slice().iter().all(|&x| x < 1e-8)
+    vector.data.as_slice().iter().all(|&x| x.abs() < 1e-8)
 }
 
 /// Convert a buffer of f64 values into an image.